@@ -0,0 +1,65 @@
+/// What a context consists of, generated as `DESCRIPTOR` next to its `bind` function and
+/// returned by [`Describe::descriptor`] for bound contexts.
+///
+/// Signatures and types are written the way they appear in the context module.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextDescriptor {
+    /// The name of the context module, e.g. `account`
+    pub name: &'static str,
+    /// The context trait returned by `bind`, e.g. `Account`
+    pub context_trait: &'static str,
+    pub roles: &'static [RoleDescriptor],
+    pub data: &'static [DataDescriptor],
+    pub interactions: &'static [MethodDescriptor],
+}
+
+/// A role field of a context and the role played through it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoleDescriptor {
+    /// The role trait, e.g. `LedgerRole`
+    pub name: &'static str,
+    /// The field of the context the player is bound to, e.g. `ledger`
+    pub field: &'static str,
+    /// The traits a player must implement, the role's contract first
+    pub contracts: &'static [&'static str],
+    /// The methods of the contract declared in the context module, empty for contracts
+    /// declared elsewhere
+    pub contract_methods: &'static [MethodDescriptor],
+    pub methods: &'static [MethodDescriptor],
+}
+
+/// A field of a context holding data rather than a role player
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataDescriptor {
+    pub name: &'static str,
+    pub ty: &'static str,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MethodDescriptor {
+    pub name: &'static str,
+    /// The signature without the body, e.g. `fn add(&mut self, entry: LedgerEntry)`
+    pub signature: &'static str,
+}
+
+impl ContextDescriptor {
+    /// The role played through `field` or named `name`
+    pub fn role(&self, name: &str) -> Option<&RoleDescriptor> {
+        self.roles.iter().find(|role| role.field == name || role.name == name)
+    }
+
+    pub fn interaction(&self, name: &str) -> Option<&MethodDescriptor> {
+        self.interactions.iter().find(|interaction| interaction.name == name)
+    }
+}
+
+impl RoleDescriptor {
+    pub fn method(&self, name: &str) -> Option<&MethodDescriptor> {
+        self.methods.iter().find(|method| method.name == name)
+    }
+}
+
+/// Implemented by every context, so a bound context can be described without naming it
+pub trait Describe {
+    fn descriptor(&self) -> &'static ContextDescriptor;
+}
@@ -0,0 +1,136 @@
+//! Hooks run around every interaction of a context declared with
+//! `#[five::context(intercept(Type))]`, for metrics, authorization checks or propagating
+//! request ids without touching the interactions themselves.
+//!
+//! ```ignore
+//! struct Metrics;
+//!
+//! impl five::Interceptor for Metrics {
+//!     fn before_interaction(interaction: &five::Interaction) {
+//!         CALLS.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! }
+//!
+//! #[five::context(intercept(crate::Metrics))]
+//! pub mod account { ... }
+//! ```
+use core::fmt::Debug;
+use core::future::Future;
+use core::marker::PhantomData;
+
+use crate::{ContextDescriptor, MethodDescriptor};
+
+/// The interaction an [`Interceptor`] is called for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Interaction {
+    pub context: &'static ContextDescriptor,
+    /// The name of the interaction, e.g. `transfer`
+    pub name: &'static str,
+}
+
+impl Interaction {
+    pub const fn new(context: &'static ContextDescriptor, name: &'static str) -> Self {
+        Interaction { context, name }
+    }
+
+    /// The name and signature of the interaction
+    pub fn descriptor(&self) -> Option<&'static MethodDescriptor> {
+        self.context.interaction(self.name)
+    }
+}
+
+/// Callbacks around the interactions of a context. The interceptor is a type rather than a
+/// value, so state such as counters or the current request id lives in statics or
+/// thread and task locals.
+///
+/// Interactions called by other interactions are intercepted as well.
+pub trait Interceptor {
+    /// Called before the body of the interaction runs
+    fn before_interaction(_interaction: &Interaction) {}
+
+    /// Called once the interaction has returned, after [`Interceptor::on_error`] for
+    /// interactions returning an error. Also called when the interaction panics or, for
+    /// async interactions, when its future is dropped before completing.
+    fn after_interaction(_interaction: &Interaction) {}
+
+    /// Called when an interaction returning a `Result` returns an `Err`
+    fn on_error(_interaction: &Interaction, _error: &dyn Debug) {}
+}
+
+/// The outcome of an interaction returning a `Result`, whose error is given to
+/// [`Interceptor::on_error`]
+#[doc(hidden)]
+pub trait Fallible {
+    fn error(&self) -> Option<&dyn Debug>;
+}
+
+impl<T, E: Debug> Fallible for Result<T, E> {
+    fn error(&self) -> Option<&dyn Debug> {
+        self.as_ref().err().map(|error| error as &dyn Debug)
+    }
+}
+
+/// Calls [`Interceptor::after_interaction`] when dropped, so it is called however the
+/// body of the interaction ends
+struct After<I: Interceptor> {
+    interaction: Interaction,
+    interceptor: PhantomData<fn() -> I>,
+}
+
+impl<I: Interceptor> After<I> {
+    fn new(interaction: Interaction) -> Self {
+        After {
+            interaction,
+            interceptor: PhantomData,
+        }
+    }
+}
+
+impl<I: Interceptor> Drop for After<I> {
+    fn drop(&mut self) {
+        I::after_interaction(&self.interaction);
+    }
+}
+
+/// Runs the body of a sync interaction between the callbacks of `I`
+#[doc(hidden)]
+pub fn intercept<I: Interceptor, R, F: FnOnce() -> R>(interaction: Interaction, body: F) -> R {
+    I::before_interaction(&interaction);
+    let _after = After::<I>::new(interaction);
+    body()
+}
+
+/// [`intercept`] for interactions returning a `Result`
+#[doc(hidden)]
+pub fn intercept_fallible<I: Interceptor, R: Fallible, F: FnOnce() -> R>(interaction: Interaction, body: F) -> R {
+    I::before_interaction(&interaction);
+    let _after = After::<I>::new(interaction);
+    let result = body();
+    if let Some(error) = result.error() {
+        I::on_error(&interaction, error);
+    }
+    result
+}
+
+/// Runs the body of an async interaction between the callbacks of `I`
+#[doc(hidden)]
+pub async fn intercept_async<I: Interceptor, R, F: Future<Output = R>>(interaction: Interaction, body: F) -> R {
+    I::before_interaction(&interaction);
+    let _after = After::<I>::new(interaction);
+    body.await
+}
+
+/// [`intercept_async`] for interactions returning a `Result`
+#[doc(hidden)]
+pub async fn intercept_async_fallible<I: Interceptor, R: Fallible, F: Future<Output = R>>(
+    interaction: Interaction,
+    body: F,
+) -> R {
+    I::before_interaction(&interaction);
+    let _after = After::<I>::new(interaction);
+    let result = body.await;
+    if let Some(error) = result.error() {
+        I::on_error(&interaction, error);
+    }
+    result
+}
@@ -0,0 +1,107 @@
+//! The runtime of the mocks generated by [`macro@crate::mock`]
+
+use std::boxed::Box;
+use std::fmt::Debug;
+use std::string::ToString;
+use std::{format, panic};
+
+// The std items named by the generated mocks, which cannot assume `::std` resolves in the
+// crate they are generated in
+#[doc(hidden)]
+pub use std::{string::String, sync::Mutex, thread::panicking, vec::Vec};
+
+/// How one method of a mock behaves and is expected to be called. `F` is the handler
+/// computing the result from the arguments, e.g. `dyn FnMut(LedgerEntry) + Send`.
+pub struct Expectation<F: ?Sized> {
+    handler: Option<Box<F>>,
+    times: Option<usize>,
+    calls: usize,
+}
+
+impl<F: ?Sized> Default for Expectation<F> {
+    fn default() -> Self {
+        Expectation {
+            handler: None,
+            times: None,
+            calls: 0,
+        }
+    }
+}
+
+impl<F: ?Sized> Expectation<F> {
+    /// Computes the results of the calls with `handler`. Borrowed results are `'static`.
+    pub fn returning(&mut self, handler: Box<F>) -> &mut Self {
+        self.handler = Some(handler);
+        self
+    }
+
+    /// Expects exactly `times` calls, checked by `verify` and when the mock is dropped
+    pub fn times(&mut self, times: usize) -> &mut Self {
+        self.times = Some(times);
+        self
+    }
+
+    pub fn never(&mut self) -> &mut Self {
+        self.times(0)
+    }
+
+    /// The number of calls made so far
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// Counts a call of `method` and returns the handler to make it with, if one was given
+    pub fn call(&mut self, method: &str) -> Option<&mut F> {
+        self.calls += 1;
+        if self.times.is_some_and(|times| self.calls > times) {
+            panic!("{} was called {} times, expected {}", method, self.calls, self.times.unwrap());
+        }
+        self.handler.as_deref_mut()
+    }
+
+    /// Panics unless `method` was called as often as expected
+    pub fn verify(&self, method: &str) {
+        if let Some(times) = self.times {
+            assert_eq!(self.calls, times, "{} was called {} times, expected {}", method, self.calls, times);
+        }
+    }
+}
+
+/// The calls made on a mock, in order, e.g. `push(Deposit("Salary", 100))`
+#[derive(Default)]
+pub struct Calls(Mutex<Vec<String>>);
+
+impl Calls {
+    pub fn record(&self, method: &str, args: &[String]) {
+        let call = format!("{}({})", method, args.join(", "));
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).push(call);
+    }
+
+    pub fn get(&self) -> Vec<String> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+}
+
+/// An argument of a mocked call, printed with `Debug` when it implements it and as `_`
+/// otherwise: `(&Arg(&arg)).describe()` prefers [`DebugArg`] over [`OpaqueArg`]
+pub struct Arg<'a, T: ?Sized>(pub &'a T);
+
+pub trait DebugArg {
+    fn describe(&self) -> String;
+}
+
+impl<T: Debug + ?Sized> DebugArg for Arg<'_, T> {
+    fn describe(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+pub trait OpaqueArg {
+    fn describe(&self) -> String;
+}
+
+impl<T: ?Sized> OpaqueArg for &Arg<'_, T> {
+    fn describe(&self) -> String {
+        "_".to_string()
+    }
+}
@@ -0,0 +1,132 @@
+//! The runtime of the recording and replaying players generated by
+//! [`macro@crate::record`]
+
+use std::string::{String, ToString};
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+use std::panic;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+pub use serde_json::Value;
+
+// The `vec!` of std for the generated players, like the std items re-exported by the mocks
+#[doc(hidden)]
+pub use std::vec;
+
+/// The contract calls made during a run, in order, as saved between runs
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Transcript {
+    pub calls: Vec<RecordedCall>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub contract: String,
+    pub method: String,
+    pub args: Vec<Value>,
+    pub result: Value,
+}
+
+impl Transcript {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Collects the calls of the recording players sharing it into one transcript
+#[derive(Clone, Default)]
+pub struct Recorder(Arc<Mutex<Transcript>>);
+
+impl Recorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, contract: &str, method: &str, args: Vec<Value>, result: Value) {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).calls.push(RecordedCall {
+            contract: contract.to_string(),
+            method: method.to_string(),
+            args,
+            result,
+        });
+    }
+
+    /// The calls recorded so far
+    pub fn transcript(&self) -> Transcript {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+}
+
+/// Answers the calls of the replaying players sharing it from a transcript, expecting
+/// them in the recorded order
+#[derive(Clone)]
+pub struct Replayer(Arc<Mutex<Replay>>);
+
+struct Replay {
+    transcript: Transcript,
+    next: usize,
+    check_args: bool,
+}
+
+impl Replayer {
+    pub fn new(transcript: Transcript) -> Self {
+        Replayer(Arc::new(Mutex::new(Replay {
+            transcript,
+            next: 0,
+            check_args: true,
+        })))
+    }
+
+    /// Only checks the contract and method of each call, for arguments that differ
+    /// between runs, e.g. because they are encrypted with a random nonce
+    pub fn ignoring_args(self) -> Self {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).check_args = false;
+        self
+    }
+
+    /// The recorded result of the next call, which must be a call of `contract::method`
+    /// with `args`
+    pub fn replay(&self, contract: &str, method: &str, args: Vec<Value>) -> Value {
+        let mut replay = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        let index = replay.next;
+        let Some(call) = replay.transcript.calls.get(index) else {
+            panic!("{}::{} was called after the {} recorded calls", contract, method, index);
+        };
+        if call.contract != contract || call.method != method {
+            panic!(
+                "call {} was recorded as {}::{}, not {}::{}",
+                index, call.contract, call.method, contract, method
+            );
+        }
+        if replay.check_args && call.args != args {
+            panic!(
+                "call {} of {}::{} was recorded with {:?}, not {:?}",
+                index, contract, method, call.args, args
+            );
+        }
+        let result = call.result.clone();
+        replay.next += 1;
+        result
+    }
+
+    /// Whether every recorded call has been replayed
+    pub fn is_finished(&self) -> bool {
+        let replay = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        replay.next == replay.transcript.calls.len()
+    }
+}
+
+/// An argument or result as recorded
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Value {
+    serde_json::to_value(value).unwrap_or_else(|err| panic!("A contract call can't be recorded: {}", err))
+}
+
+/// A recorded result
+pub fn from_value<T: DeserializeOwned>(value: Value) -> T {
+    serde_json::from_value(value).unwrap_or_else(|err| panic!("A recorded result doesn't fit the contract: {}", err))
+}
@@ -0,0 +1,113 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+use five_core::analysis::{Call, Callee, InteractionCalls};
+
+/// Mermaid sequence diagrams, one per interaction, as fenced blocks of a Markdown document
+pub fn mermaid(context_trait: &str, interactions: &[InteractionCalls]) -> String {
+    let mut text = String::new();
+    for interaction in interactions {
+        writeln!(text, "## {}::{}\n", context_trait, interaction.interaction).unwrap();
+        writeln!(text, "```mermaid\nsequenceDiagram").unwrap();
+        writeln!(text, "    participant Context as {}", context_trait).unwrap();
+
+        // Roles in the order they take part
+        let mut fields = vec![];
+        collect_fields(&interaction.calls, &mut fields);
+        for (field, role) in &fields {
+            writeln!(text, "    participant {} as {}: {}", field, field, role).unwrap();
+        }
+
+        sequence("Context", &interaction.calls, &mut text);
+        writeln!(text, "```\n").unwrap();
+    }
+    text
+}
+
+/// The role fields called, with the role played through them
+fn collect_fields(calls: &[Call], fields: &mut Vec<(String, String)>) {
+    for call in calls {
+        let field = match &call.callee {
+            Callee::Interaction { .. } => None,
+            Callee::RoleMethod { field, role, .. } | Callee::PlayerMethod { field, role, .. } => {
+                Some((field, role))
+            }
+        };
+        if let Some((field, role)) = field {
+            if !fields.iter().any(|(known, _)| known == field) {
+                fields.push((field.clone(), role.clone()));
+            }
+        }
+        collect_fields(&call.calls, fields);
+    }
+}
+
+fn sequence(caller: &str, calls: &[Call], text: &mut String) {
+    for call in calls {
+        let recursive = if call.recursive { " (recursive)" } else { "" };
+        let callee = match &call.callee {
+            Callee::Interaction { method } => {
+                writeln!(text, "    {}->>Context: {}{}", caller, method, recursive).unwrap();
+                "Context"
+            }
+            Callee::RoleMethod { field, method, .. } => {
+                writeln!(text, "    {}->>{}: {}{}", caller, field, method, recursive).unwrap();
+                field
+            }
+            Callee::PlayerMethod { field, contract, method, .. } => {
+                writeln!(text, "    {}->>{}: {}::{}", caller, field, contract, method).unwrap();
+                field
+            }
+        };
+        if !call.calls.is_empty() {
+            writeln!(text, "    activate {}", callee).unwrap();
+            sequence(callee, &call.calls, text);
+            writeln!(text, "    deactivate {}", callee).unwrap();
+        }
+    }
+}
+
+/// A Graphviz call graph of the whole context, from interactions through role methods to
+/// the contract methods of the players
+pub fn dot(context_trait: &str, interactions: &[InteractionCalls]) -> String {
+    let mut nodes = BTreeSet::new();
+    let mut edges = BTreeSet::new();
+    for interaction in interactions {
+        let node = format!("{}::{}", context_trait, interaction.interaction);
+        nodes.insert((node.clone(), "box"));
+        graph(context_trait, &node, &interaction.calls, &mut nodes, &mut edges);
+    }
+
+    let mut text = String::new();
+    writeln!(text, "digraph \"{}\" {{", context_trait).unwrap();
+    writeln!(text, "    rankdir=LR;").unwrap();
+    for (node, shape) in &nodes {
+        writeln!(text, "    \"{}\" [shape={}];", node, shape).unwrap();
+    }
+    for (from, to) in &edges {
+        writeln!(text, "    \"{}\" -> \"{}\";", from, to).unwrap();
+    }
+    writeln!(text, "}}").unwrap();
+    text
+}
+
+fn graph(
+    context_trait: &str,
+    caller: &str,
+    calls: &[Call],
+    nodes: &mut BTreeSet<(String, &'static str)>,
+    edges: &mut BTreeSet<(String, String)>,
+) {
+    for call in calls {
+        let (node, shape) = match &call.callee {
+            Callee::Interaction { method } => (format!("{}::{}", context_trait, method), "box"),
+            Callee::RoleMethod { field, role, method } => (format!("{}: {}::{}", field, role, method), "ellipse"),
+            Callee::PlayerMethod { field, contract, method, .. } => {
+                (format!("{}: {}::{}", field, contract, method), "note")
+            }
+        };
+        nodes.insert((node.clone(), shape));
+        edges.insert((caller.to_string(), node.clone()));
+        graph(context_trait, &node, &call.calls, nodes, edges);
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use five_core::analysis::context_trait_name;
+use five_core::analysis::errors::MacroError;
+
+use crate::sources::ContextSource;
+
+/// The generated code of one context
+pub struct Expansion {
+    /// The generated items, pretty-printed
+    pub code: String,
+    /// The methods of the context as written next to their generated forms
+    pub mapping: String,
+}
+
+impl Expansion {
+    pub fn new(source: &ContextSource) -> Result<Self, Vec<MacroError>> {
+        let expanded = five_core::expand(source.tokens())?;
+        let generated: syn::File = syn::parse2(expanded)
+            .unwrap_or_else(|err| panic!("The generated code should parse: {}", err));
+        Ok(Expansion {
+            code: prettyplease::unparse(&generated),
+            mapping: mapping(&source.module, &generated),
+        })
+    }
+
+    /// Writes the code and the mapping to `<dir>/<context>.rs` and `<dir>/<context>.map.txt`,
+    /// returning the paths written
+    pub fn write(&self, dir: &Path, name: &str) -> std::io::Result<(PathBuf, PathBuf)> {
+        std::fs::create_dir_all(dir)?;
+        let code = dir.join(format!("{}.rs", name));
+        let mapping = dir.join(format!("{}.map.txt", name));
+        std::fs::write(&code, &self.code)?;
+        std::fs::write(&mapping, &self.mapping)?;
+        Ok((code, mapping))
+    }
+}
+
+/// The directory generated code is written to, `target/five` of the workspace containing
+/// `path` unless `CARGO_TARGET_DIR` says otherwise
+pub fn output_dir(path: &Path) -> PathBuf {
+    let target = std::env::var_os("CARGO_TARGET_DIR").map(PathBuf::from).unwrap_or_else(|| {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        // The outermost manifest is the workspace's, where `target` lives
+        path.ancestors()
+            .filter(|ancestor| ancestor.join("Cargo.toml").is_file())
+            .last()
+            .unwrap_or(&path)
+            .join("target")
+    });
+    target.join("five")
+}
+
+/// Lays out every role method and interaction next to the function generated for it,
+/// user code on the left
+fn mapping(module: &syn::ItemMod, generated: &syn::File) -> String {
+    let context_trait = context_trait_name(&module.ident).to_string();
+
+    // The generated items are wrapped in a module of the same name as the user's
+    let generated_items = generated.items.iter().flat_map(|item| match item {
+        syn::Item::Mod(item_mod) if item_mod.ident == module.ident => {
+            item_mod.content.iter().flat_map(|(_, items)| items).collect()
+        }
+        _ => vec![item],
+    });
+
+    // Generated functions by the trait they implement and their name
+    let mut generated_fns: HashMap<(String, String), String> = HashMap::new();
+    for item in generated_items {
+        let syn::Item::Impl(item_impl) = item else {
+            continue;
+        };
+        let Some((_, path, _)) = &item_impl.trait_ else {
+            continue;
+        };
+        let owner = path.segments.last().unwrap().ident.to_string();
+        for impl_item in &item_impl.items {
+            if let syn::ImplItem::Fn(function) = impl_item {
+                let item_fn = item_fn(function.attrs.clone(), function.sig.clone(), function.block.clone());
+                generated_fns.insert((owner.clone(), function.sig.ident.to_string()), item_fn);
+            }
+        }
+    }
+
+    let mut mapping = String::new();
+    let items = module.content.iter().flat_map(|(_, items)| items);
+    for item in items {
+        let (owner, generated_owner, user_fns) = match item {
+            syn::Item::Trait(item_trait) if item_trait.ident.to_string().ends_with("Role") => {
+                let user_fns: Vec<(String, String)> = item_trait
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        syn::TraitItem::Fn(syn::TraitItemFn { attrs, sig, default: Some(block), .. }) => {
+                            Some((sig.ident.to_string(), item_fn(attrs.clone(), sig.clone(), block.clone())))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                (item_trait.ident.to_string(), item_trait.ident.to_string(), user_fns)
+            }
+            syn::Item::Impl(item_impl) if is_context(&item_impl.self_ty) => {
+                let user_fns = item_impl
+                    .items
+                    .iter()
+                    .filter_map(|item| match item {
+                        syn::ImplItem::Fn(function) => Some((
+                            function.sig.ident.to_string(),
+                            item_fn(function.attrs.clone(), function.sig.clone(), function.block.clone()),
+                        )),
+                        _ => None,
+                    })
+                    .collect();
+                ("Context".to_string(), context_trait.clone(), user_fns)
+            }
+            _ => continue,
+        };
+        for (name, user_fn) in user_fns {
+            let generated_fn = generated_fns
+                .get(&(generated_owner.clone(), name.clone()))
+                .map(String::as_str)
+                .unwrap_or("// not generated");
+            writeln!(mapping, "== {}::{} -> {}::{}", owner, name, generated_owner, name).unwrap();
+            mapping.push_str(&side_by_side(&user_fn, generated_fn));
+            mapping.push('\n');
+        }
+    }
+    mapping
+}
+
+fn is_context(ty: &syn::Type) -> bool {
+    matches!(ty, syn::Type::Path(type_path)
+        if type_path.path.segments.last().is_some_and(|segment| segment.ident == "Context"))
+}
+
+/// A function pretty-printed on its own
+fn item_fn(attrs: Vec<syn::Attribute>, sig: syn::Signature, block: syn::Block) -> String {
+    let item = syn::Item::Fn(syn::ItemFn {
+        attrs,
+        vis: syn::Visibility::Inherited,
+        sig,
+        block: Box::new(block),
+    });
+    prettyplease::unparse(&syn::File {
+        shebang: None,
+        attrs: vec![],
+        items: vec![item],
+    })
+}
+
+/// Two blocks of text in columns, `left` padded to its widest line
+fn side_by_side(left: &str, right: &str) -> String {
+    let left: Vec<&str> = left.lines().collect();
+    let right: Vec<&str> = right.lines().collect();
+    let width = left.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let mut text = String::new();
+    for i in 0..left.len().max(right.len()) {
+        let left = left.get(i).copied().unwrap_or("");
+        let right = right.get(i).copied().unwrap_or("");
+        let line = format!("{:width$} | {}", left, right, width = width);
+        writeln!(text, "{}", line.trim_end()).unwrap();
+    }
+    text
+}
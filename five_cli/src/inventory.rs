@@ -0,0 +1,134 @@
+use std::fmt::Write;
+
+use five_core::analysis::{compact, context_trait_name, role_generic_name, FunctionDescription, ModuleInfo, RoleField};
+use serde::Serialize;
+
+use crate::sources::ContextSource;
+
+/// The DCI model of a context: who plays which role, what the players must provide and
+/// which interactions the context offers
+#[derive(Serialize)]
+pub struct ContextInventory {
+    pub name: String,
+    pub module: String,
+    pub file: String,
+    /// The context trait returned by `bind`, with its generic parameters
+    pub context_trait: String,
+    pub roles: Vec<RoleInventory>,
+    pub data: Vec<DataInventory>,
+    pub interactions: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct RoleInventory {
+    pub name: String,
+    pub field: String,
+    /// The generic parameter of the context standing in for the player
+    pub player: String,
+    /// The traits a player must implement
+    pub contracts: Vec<String>,
+    pub methods: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct DataInventory {
+    pub name: String,
+    pub ty: String,
+}
+
+impl ContextInventory {
+    pub fn new(source: &ContextSource, module: &ModuleInfo) -> Self {
+        let context = &module.context;
+
+        let roles: Vec<RoleInventory> = context
+            .role_fields()
+            .into_iter()
+            .map(|RoleField { field, role_name, role, contracts }| {
+                let methods = role
+                    .map(|role| role.methods.iter().map(FunctionDescription::signature).collect())
+                    .unwrap_or_default();
+                RoleInventory {
+                    name: role_name.to_string(),
+                    field: field.to_string(),
+                    player: role_generic_name(&field).to_string(),
+                    contracts,
+                    methods,
+                }
+            })
+            .collect();
+
+        let data = context
+            .properties
+            .iter()
+            .filter(|prop| !prop.is_role())
+            .map(|prop| DataInventory {
+                name: prop.get_name().to_string(),
+                ty: compact(&prop.get_ty()),
+            })
+            .collect();
+
+        let generics: Vec<String> = context
+            .generics
+            .get_params()
+            .iter()
+            .map(|param| match param {
+                syn::GenericParam::Type(param) => param.ident.to_string(),
+                syn::GenericParam::Lifetime(param) => param.lifetime.to_string(),
+                syn::GenericParam::Const(param) => param.ident.to_string(),
+            })
+            .chain(roles.iter().map(|role| role.player.clone()))
+            .collect();
+        let trait_name = context_trait_name(&module.module_name);
+        let context_trait = if generics.is_empty() {
+            trait_name.to_string()
+        } else {
+            format!("{}<{}>", trait_name, generics.join(", "))
+        };
+
+        ContextInventory {
+            name: module.module_name.to_string(),
+            module: source.module_path.clone(),
+            file: source.file.display().to_string(),
+            context_trait,
+            roles,
+            data,
+            interactions: context
+                .impl_blocks
+                .iter()
+                .flat_map(|block| &block.functions)
+                .map(FunctionDescription::signature)
+                .collect(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        writeln!(text, "context {} ({}, {})", self.name, self.module, self.file).unwrap();
+        writeln!(text, "  trait {}", self.context_trait).unwrap();
+        if !self.roles.is_empty() {
+            writeln!(text, "  roles:").unwrap();
+        }
+        for role in &self.roles {
+            writeln!(text, "    {}: {} played by {}", role.field, role.name, role.player).unwrap();
+            if !role.contracts.is_empty() {
+                writeln!(text, "      requires {}", role.contracts.join(" + ")).unwrap();
+            }
+            for method in &role.methods {
+                writeln!(text, "      {}", method).unwrap();
+            }
+        }
+        if !self.data.is_empty() {
+            writeln!(text, "  data:").unwrap();
+        }
+        for data in &self.data {
+            writeln!(text, "    {}: {}", data.name, data.ty).unwrap();
+        }
+        if !self.interactions.is_empty() {
+            writeln!(text, "  interactions:").unwrap();
+        }
+        for interaction in &self.interactions {
+            writeln!(text, "    {}", interaction).unwrap();
+        }
+        text
+    }
+}
@@ -0,0 +1,6 @@
+//! The commands of the `five` binary, usable as a library by other tools
+pub mod diagram;
+pub mod expand;
+pub mod inventory;
+
+pub use five_core::{manifest, sources};
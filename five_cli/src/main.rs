@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use five_cli::diagram;
+use five_core::analysis::{analyze_call_graph, context_trait_name};
+use five_cli::expand::{output_dir, Expansion};
+use five_cli::inventory::ContextInventory;
+use five_cli::manifest::{crate_name, Manifest};
+use five_cli::sources;
+
+/// Tools for crates using five DCI contexts
+#[derive(Parser)]
+#[command(name = "five")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Lists every context of a crate with its roles, contracts, data and interactions
+    Inventory {
+        /// The crate directory, a source directory or a single source file
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+    /// Writes the pretty-printed code generated for one context to `target/five/`, with a
+    /// mapping from its methods to their generated forms
+    Expand {
+        /// The name of the context module, optionally qualified, e.g. `crate::account::account`
+        context: String,
+        /// The crate directory, a source directory or a single source file
+        #[arg(default_value = ".")]
+        path: PathBuf,
+    },
+    /// Writes a JSON manifest of the contexts of a crate, `target/five/manifest.json` unless
+    /// `--out` is given
+    Manifest {
+        /// The crate directory, a source directory or a single source file
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// The file to write
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Prints the call graph of a context's interactions, through its role methods to the
+    /// methods of the players
+    Diagram {
+        /// The name of the context module, optionally qualified, e.g. `crate::account::account`
+        context: String,
+        /// The crate directory, a source directory or a single source file
+        #[arg(default_value = ".")]
+        path: PathBuf,
+        /// Mermaid sequence diagrams per interaction, or one Graphviz graph
+        #[arg(long, value_enum, default_value_t = Format::Mermaid)]
+        format: Format,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Mermaid,
+    Dot,
+}
+
+fn main() -> ExitCode {
+    match Cli::parse().command {
+        Command::Inventory { path, json } => inventory(path, json),
+        Command::Expand { context, path } => expand(context, path),
+        Command::Manifest { path, out } => manifest(path, out),
+        Command::Diagram { context, path, format } => diagram(context, path, format),
+    }
+}
+
+fn inventory(path: PathBuf, json: bool) -> ExitCode {
+    let sources = match sources::find_contexts(&path) {
+        Ok(sources) => sources,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut failed = false;
+    let mut contexts = vec![];
+    for source in &sources {
+        match five_core::analyze(source.tokens()) {
+            Ok(module) => contexts.push(ContextInventory::new(source, &module)),
+            Err(errors) => {
+                failed = true;
+                for err in errors {
+                    eprintln!("{}: context `{}`: {}", source.file.display(), source.module.ident, err);
+                }
+            }
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&contexts).unwrap());
+    } else {
+        for context in &contexts {
+            println!("{}", context.to_text());
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn expand(context: String, path: PathBuf) -> ExitCode {
+    let sources = match sources::find_contexts(&path) {
+        Ok(sources) => sources,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let source = match sources::select_context(&sources, &context) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{} in {}", err, path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let expansion = match Expansion::new(source) {
+        Ok(expansion) => expansion,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("{}: context `{}`: {}", source.file.display(), source.module.ident, err);
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+    match expansion.write(&output_dir(&path), &source.module.ident.to_string()) {
+        Ok((code, mapping)) => {
+            println!("Wrote {}", code.display());
+            println!("Wrote {}", mapping.display());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to write the expansion: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn manifest(path: PathBuf, out: Option<PathBuf>) -> ExitCode {
+    let sources = match sources::find_contexts(&path) {
+        Ok(sources) => sources,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let manifest = match Manifest::from_sources(crate_name(&path), &sources) {
+        Ok(manifest) => manifest,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("{}", err);
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+    let out = out.unwrap_or_else(|| output_dir(&path).join("manifest.json"));
+    match manifest.write(&out) {
+        Ok(()) => {
+            println!("Wrote {}", out.display());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to write the manifest: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn diagram(context: String, path: PathBuf, format: Format) -> ExitCode {
+    let sources = match sources::find_contexts(&path) {
+        Ok(sources) => sources,
+        Err(err) => {
+            eprintln!("Failed to read {}: {}", path.display(), err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let source = match sources::select_context(&sources, &context) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("{} in {}", err, path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let module = match five_core::analyze(source.tokens()) {
+        Ok(module) => module,
+        Err(errors) => {
+            for err in errors {
+                eprintln!("{}: context `{}`: {}", source.file.display(), source.module.ident, err);
+            }
+            return ExitCode::FAILURE;
+        }
+    };
+    let context_trait = context_trait_name(&source.module.ident).to_string();
+    let interactions = analyze_call_graph(&module.context);
+    match format {
+        Format::Mermaid => print!("{}", diagram::mermaid(&context_trait, &interactions)),
+        Format::Dot => print!("{}", diagram::dot(&context_trait, &interactions)),
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+
+use syn::punctuated::Punctuated;
+use syn::visit_mut::VisitMut;
+use syn::Expr;
+
+use super::players::{self, Players, VisitPlayers};
+use super::*;
+
+/// What an interaction or role method calls
+#[derive(Clone, Debug, PartialEq)]
+pub enum Callee {
+    /// An interaction of the context
+    Interaction { method: String },
+    /// A method of the role played by the object in `field`
+    RoleMethod { field: String, role: String, method: String },
+    /// A method the object in `field` provides as the player of `role`, usually one of the
+    /// role's contract
+    PlayerMethod { field: String, role: String, contract: String, method: String },
+}
+
+/// A call made by an interaction or role method, with the calls the callee makes in turn
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub callee: Callee,
+    pub calls: Vec<Call>,
+    /// Whether the callee is already being called further up, so its calls aren't repeated
+    pub recursive: bool,
+}
+
+/// The calls made by an interaction, in the order they are evaluated
+#[derive(Clone, Debug)]
+pub struct InteractionCalls {
+    pub interaction: String,
+    pub calls: Vec<Call>,
+}
+
+/// Follows the calls of every interaction through role methods down to the methods of the
+/// players, the way `rewrite_role_access` finds the role calls it rewrites
+pub fn analyze_call_graph(context: &ContextInfo) -> Vec<InteractionCalls> {
+    let graph = CallGraph::new(context);
+    graph
+        .interactions
+        .iter()
+        .map(|(name, body)| {
+            let mut stack = vec![Callee::Interaction { method: name.clone() }];
+            InteractionCalls {
+                interaction: name.clone(),
+                calls: graph.calls(body, None, &mut stack),
+            }
+        })
+        .collect()
+}
+
+/// The calls made by a role method, in the order they are evaluated
+#[derive(Clone, Debug)]
+pub struct RoleMethodCalls {
+    pub field: String,
+    pub role: String,
+    pub method: String,
+    pub calls: Vec<Call>,
+}
+
+/// Follows the calls of every role method played through a field of the context
+pub fn analyze_role_method_calls(context: &ContextInfo) -> Vec<RoleMethodCalls> {
+    let graph = CallGraph::new(context);
+    let graph = &graph;
+    context
+        .properties
+        .iter()
+        .filter_map(|prop| {
+            let field = prop.get_name().to_string();
+            let role = graph.role(graph.role_fields.get(&field)?)?;
+            Some((field, role))
+        })
+        .flat_map(|(field, role)| {
+            role.methods.iter().filter_map(move |function| match function {
+                FunctionDescription::Implementation { name, body, .. } => {
+                    let callee = Callee::RoleMethod {
+                        field: field.clone(),
+                        role: role.name.to_string(),
+                        method: name.to_string(),
+                    };
+                    Some(RoleMethodCalls {
+                        field: field.clone(),
+                        role: role.name.to_string(),
+                        method: name.to_string(),
+                        calls: graph.calls(body, Some(&field), &mut vec![callee]),
+                    })
+                }
+                FunctionDescription::Declaration { .. } => None,
+            })
+        })
+        .collect()
+}
+
+/// The methods of players called by `calls` or the calls they make in turn, each once
+pub fn player_method_calls(calls: &[Call]) -> Vec<Callee> {
+    fn collect(calls: &[Call], found: &mut Vec<Callee>) {
+        for call in calls {
+            if matches!(call.callee, Callee::PlayerMethod { .. }) && !found.contains(&call.callee) {
+                found.push(call.callee.clone());
+            }
+            collect(&call.calls, found);
+        }
+    }
+    let mut found = vec![];
+    collect(calls, &mut found);
+    found
+}
+
+struct CallGraph<'a> {
+    context: &'a ContextInfo,
+    // Interactions with their bodies, in source order
+    interactions: Vec<(String, &'a syn::Block)>,
+    // Role fields with the role played through them
+    role_fields: HashMap<String, String>,
+}
+
+impl<'a> CallGraph<'a> {
+    fn new(context: &'a ContextInfo) -> Self {
+        let interactions = context
+            .impl_blocks
+            .iter()
+            .flat_map(|block| &block.functions)
+            .filter_map(|function| match function {
+                FunctionDescription::Implementation { name, body, .. } => Some((name.to_string(), body)),
+                FunctionDescription::Declaration { .. } => None,
+            })
+            .collect();
+        let role_fields = context
+            .role_fields()
+            .into_iter()
+            .map(|role_field| (role_field.field.to_string(), role_field.role_name.to_string()))
+            .collect();
+        CallGraph {
+            context,
+            interactions,
+            role_fields,
+        }
+    }
+
+    fn role(&self, name: &str) -> Option<&'a Role> {
+        self.context.roles.iter().find(|role| role.name == name)
+    }
+
+    /// The calls made by `body`, which belongs to an interaction or, with `field`, to a
+    /// method of the role played through `field`
+    fn calls(&self, body: &syn::Block, field: Option<&str>, stack: &mut Vec<Callee>) -> Vec<Call> {
+        // Receivers are classified like `rewrite_role_access` does, by a visitor that works on
+        // a copy of the body
+        let mut visitor = CallVisitor {
+            graph: self,
+            players: Players::new(self.role_fields.keys().cloned(), field.map(str::to_string)),
+            callees: vec![],
+        };
+        visitor.visit_block_mut(&mut body.clone());
+
+        visitor
+            .callees
+            .into_iter()
+            .map(|callee| {
+                let recursive = stack.contains(&callee);
+                let calls = match (&callee, recursive) {
+                    (_, true) => vec![],
+                    (Callee::Interaction { method }, false) => {
+                        let body = self.interactions.iter().find(|(name, _)| name == method).map(|(_, body)| *body);
+                        self.nested(&callee, body, None, stack)
+                    }
+                    (Callee::RoleMethod { field, role, method }, false) => {
+                        let body = self.role(role).and_then(|role| {
+                            role.methods.iter().find_map(|function| match function {
+                                FunctionDescription::Implementation { name, body, .. } if name == method => Some(body),
+                                _ => None,
+                            })
+                        });
+                        self.nested(&callee, body, Some(field), stack)
+                    }
+                    (Callee::PlayerMethod { .. }, false) => vec![],
+                };
+                Call { callee, calls, recursive }
+            })
+            .collect()
+    }
+
+    fn nested(&self, callee: &Callee, body: Option<&syn::Block>, field: Option<&str>, stack: &mut Vec<Callee>) -> Vec<Call> {
+        let Some(body) = body else {
+            return vec![];
+        };
+        stack.push(callee.clone());
+        let calls = self.calls(body, field, stack);
+        stack.pop();
+        calls
+    }
+
+    /// The call of `method` on the object in the role field `field`
+    fn field_call(&self, field: &str, method: &syn::Ident) -> Option<Callee> {
+        let role_name = self.role_fields.get(field)?;
+        let role = self.role(role_name);
+        if role.is_none_or(|role| role.has_method(method)) {
+            return Some(Callee::RoleMethod {
+                field: field.to_string(),
+                role: role_name.clone(),
+                method: method.to_string(),
+            });
+        }
+        Some(Callee::PlayerMethod {
+            field: field.to_string(),
+            role: role_name.clone(),
+            contract: role?.contract_of(method),
+            method: method.to_string(),
+        })
+    }
+
+    fn is_interaction(&self, method: &syn::Ident) -> bool {
+        self.interactions.iter().any(|(name, _)| method == name)
+    }
+}
+
+struct CallVisitor<'a, 'g> {
+    graph: &'g CallGraph<'a>,
+    players: Players,
+    callees: Vec<Callee>,
+}
+
+impl VisitPlayers for CallVisitor<'_, '_> {
+    fn players(&mut self) -> &mut Players {
+        &mut self.players
+    }
+}
+
+impl VisitMut for CallVisitor<'_, '_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        // The receiver and arguments are evaluated before the call is made
+        players::visit_expr_mut(self, expr);
+        let callee = match expr {
+            Expr::MethodCall(call) => {
+                if self.players.is_context(&call.receiver) && self.graph.is_interaction(&call.method) {
+                    Some(Callee::Interaction { method: call.method.to_string() })
+                } else {
+                    self.players
+                        .player(&call.receiver)
+                        .and_then(|player| self.graph.field_call(player.field(), &call.method))
+                }
+            }
+            // `LedgerRole::add(&mut self.ledger, entry)`
+            Expr::Call(call) => match &*call.func {
+                Expr::Path(path) => match path.path.segments.iter().collect::<Vec<_>>().as_slice() {
+                    [role, method] if self.graph.role(&role.ident.to_string()).is_some() => call
+                        .args
+                        .first()
+                        .and_then(|player| self.players.player(player))
+                        .and_then(|player| self.graph.field_call(player.field(), &method.ident)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        self.callees.extend(callee);
+    }
+
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        players::visit_block_mut(self, block);
+    }
+
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        players::visit_local_mut(self, local);
+    }
+
+    fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
+        // Arguments of macros like `println!` are visited when they parse as expressions
+        if let Ok(mut args) = mac.parse_body_with(Punctuated::<Expr, syn::Token![,]>::parse_terminated) {
+            args.iter_mut().for_each(|arg| self.visit_expr_mut(arg));
+        }
+    }
+}
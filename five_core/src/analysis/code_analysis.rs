@@ -0,0 +1,188 @@
+use syn::Ident;
+
+use super::*;
+
+#[derive(Clone)]
+pub struct ModuleInfo {
+    pub module_name: Ident,
+    pub vis: syn::Visibility,
+    pub attrs: Vec<syn::Attribute>,
+    pub context: ContextInfo,
+    pub others: Vec<TypeDescription>,
+    pub attributes: ContextAttributes,
+}
+
+pub fn analyze_module(module: &syn::ItemMod, attributes: ContextAttributes) -> ModuleInfo {
+    let mut roles = Vec::new();
+    let mut contexts = Vec::new();
+    let mut others = Vec::new();
+    let module_name = module.ident.clone();
+    let mut async_trait = None;
+    if let Some((_, items)) = &module.content {
+        let mut contracts = Vec::new();
+        let mut imported_contracts = Vec::new();
+        let mut impl_blocks_by_type: std::collections::HashMap<String, Vec<syn::ItemImpl>> =
+            std::collections::HashMap::new();
+
+        // Separate contracts and roles for association
+        for item in items {
+            match item {
+                syn::Item::Trait(item_trait) if item_trait.ident.to_string().ends_with("Contract") => {
+                    if async_trait.is_none() {
+                        async_trait = item_trait.attrs.iter().find(|attr| is_async_trait(attr)).cloned();
+                    }
+                    contracts.push(analyze_trait(item_trait));
+                }
+                syn::Item::Use(item_use) => analyze_imported_contracts(item_use, &mut imported_contracts),
+                syn::Item::Impl(item_impl) => {
+                    // Get the self type of the impl block
+                    if let syn::Type::Path(type_path) = &*item_impl.self_ty {
+                        if let Some(segment) = type_path.path.segments.last() {
+                            let type_name = segment.ident.to_string();
+                            impl_blocks_by_type
+                                .entry(type_name)
+                                .or_default()
+                                .push(item_impl.clone());
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for item in items {
+            match item {
+                syn::Item::Trait(item_trait) => {
+                    if item_trait.ident.to_string().ends_with("Role") {
+                        let role_name = item_trait.ident.to_string();
+                        let contract_name =
+                            role_name.strip_suffix("Role").unwrap_or("").to_string() + "Contract";
+
+                        // Find the matching contract
+                        let contract = contracts.iter().find(|contract| {
+                            contract.name == syn::Ident::new(&contract_name, contract.name.span())
+                        });
+
+                        // A contract defined elsewhere is named explicitly with
+                        // `#[role(contract = ...)]` or imported by a `use` item
+                        let external_contract = analyze_role_attribute(item_trait).or_else(|| {
+                            if contract.is_some() {
+                                return None;
+                            }
+                            imported_contracts
+                                .iter()
+                                .find(|imported| **imported == contract_name)
+                                .map(|imported| imported.clone().into())
+                        });
+
+                        // Roles without a contract rely on their supertraits alone
+                        roles.push(Role {
+                            name: item_trait.ident.clone(),
+                            contract: contract.cloned(),
+                            external_contract,
+                            supertraits: item_trait.supertraits.clone(),
+                            supertrait_contracts: contracts
+                                .iter()
+                                .filter(|contract| {
+                                    item_trait.supertraits.iter().any(|bound| match bound {
+                                        syn::TypeParamBound::Trait(trait_bound) => trait_bound
+                                            .path
+                                            .segments
+                                            .last()
+                                            .is_some_and(|segment| segment.ident == contract.name),
+                                        _ => false,
+                                    })
+                                })
+                                .cloned()
+                                .collect(),
+                            methods: analyze_trait_methods(item_trait),
+                            borrows: std::collections::HashMap::new(),
+                        });
+                    } else if let Some(contract) = contracts
+                        .iter()
+                        .find(|contract| contract.name == item_trait.ident)
+                    {
+                        others.push(TypeDescription::RoleContract(contract.clone()));
+                    } else {
+                        others.push(TypeDescription::Other(item.clone()));
+                    }
+                }
+                syn::Item::Struct(item_struct) => {
+                    if item_struct.ident == "Context" {
+                        let impl_blocks = impl_blocks_by_type
+                            .get(&item_struct.ident.to_string())
+                            .cloned()
+                            .unwrap_or_else(Vec::new);
+                        contexts.push(analyze_context(item_struct, &impl_blocks));
+                        if contexts.len() != 1 {
+                            panic!("There should be exactly one Context struct");
+                        }
+                    }
+                }
+                syn::Item::Impl(item_impl) => {
+                    // Check if the `impl` is for the `Context` type
+                    if let syn::Type::Path(type_path) = &*item_impl.self_ty {
+                        if let Some(segment) = type_path.path.segments.last() {
+                            if segment.ident == "Context" {
+                                // Skip adding to `others`
+                                continue;
+                            }
+                        }
+                    }
+                    others.push(TypeDescription::Other(item.clone()));
+                }
+                _ => {
+                    others.push(TypeDescription::Other(item.clone()));
+                }
+            }
+        }
+    }
+    // Contracts of a role are emitted together with the role
+    others.retain(|other| match other {
+        TypeDescription::RoleContract(contract) => !roles.iter().any(|role| {
+            role.contract
+                .as_ref()
+                .is_some_and(|role_contract| role_contract.name == contract.name)
+        }),
+        _ => true,
+    });
+    if contexts.len() != 1 {
+        panic!(
+            "There should be exactly one Context struct. Found {}",
+            contexts.len()
+        );
+    }
+    let mut context = contexts[0].clone();
+    for role in roles.iter_mut() {
+        role.borrows = analyze_role_borrows(role, &context.properties);
+    }
+    context.roles = roles;
+    if let Some((_, items)) = &module.content {
+        check_generated_names(&module_name, items, &context);
+    }
+    // With `send`, a context whose contracts use `#[async_trait]` boxes its futures the same way
+    context.async_strategy = match (async_trait, attributes.send) {
+        (Some(attr), true) => AsyncStrategy::AsyncTrait(Box::new(attr)),
+        (None, true) => AsyncStrategy::Send,
+        (_, false) => AsyncStrategy::Native,
+    };
+    context.interceptor = attributes.intercept.clone();
+    context.crate_path = attributes.crate_path();
+
+    ModuleInfo {
+        module_name,
+        vis: module.vis.clone(),
+        attrs: module.attrs.clone(),
+        context,
+        others,
+        attributes,
+    }
+}
+
+/// Whether `attr` is `#[async_trait]`, however the macro is referred to
+fn is_async_trait(attr: &syn::Attribute) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| segment.ident == "async_trait")
+}
@@ -0,0 +1,171 @@
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, Ident, Path, Signature, Token, Type, WherePredicate};
+
+/// The arguments given to `#[five::context(...)]`
+#[derive(Clone, Default)]
+pub struct ContextAttributes {
+    pub provides: Vec<ProvidedContract>,
+    /// `send`: async interactions and role methods return `Send` futures, boxed by
+    /// `#[async_trait]` if the contracts use it. The players must be `Send + Sync`
+    pub send: bool,
+    /// `trace`: interactions and role methods run in `tracing` spans, contract calls are
+    /// recorded as events
+    pub trace: bool,
+    /// `intercept(Type)`: the interceptor run around every interaction
+    pub intercept: Option<Type>,
+    /// `crate = path`: the path of the `five` crate in generated code, for crates that
+    /// depend on it under another name or use it through a re-export
+    pub crate_path: Option<Path>,
+}
+
+impl ContextAttributes {
+    /// The path generated code refers to the `five` crate by, `::five` by default
+    pub fn crate_path(&self) -> Path {
+        self.crate_path.clone().unwrap_or_else(|| syn::parse_quote!(::five))
+    }
+}
+
+/// A contract of another context that this context can play a role in.
+///
+/// ```ignore
+/// #[five::context(provides(
+///     #[async_trait::async_trait]
+///     crate::user_profile_context::UserStorageContract where TContext = UserProfile {
+///         async fn store(&self, key: String, data: UserProfile) -> Result<String, String> => store(key, &data);
+///         async fn retrieve(&self, key: String) -> Result<UserProfile, String>;
+///     }
+/// ))]
+/// ```
+///
+/// `TContext = UserProfile` fixes a generic of the context for the implementation, any other
+/// where predicate is added to the generated impl as-is. A contract method without a mapping
+/// is forwarded to the interaction with the same name and the same arguments.
+#[derive(Clone)]
+pub struct ProvidedContract {
+    pub attrs: Vec<Attribute>,
+    pub contract: Path,
+    pub substitutions: Vec<(Ident, Type)>,
+    pub predicates: Vec<WherePredicate>,
+    pub methods: Vec<ProvidedMethod>,
+}
+
+/// A contract method and the interaction that fulfils it
+#[derive(Clone)]
+pub struct ProvidedMethod {
+    pub signature: Signature,
+    pub interaction: Ident,
+    pub args: Punctuated<Expr, Token![,]>,
+}
+
+impl Parse for ContextAttributes {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attributes = ContextAttributes::default();
+        while !input.is_empty() {
+            if input.peek(Token![crate]) {
+                input.parse::<Token![crate]>()?;
+                input.parse::<Token![=]>()?;
+                attributes.crate_path = Some(input.parse()?);
+                if !input.is_empty() {
+                    input.parse::<Token![,]>()?;
+                }
+                continue;
+            }
+            let name: Ident = input.parse()?;
+            if name == "send" {
+                attributes.send = true;
+            } else if name == "trace" {
+                attributes.trace = true;
+            } else if name == "intercept" {
+                let content;
+                syn::parenthesized!(content in input);
+                attributes.intercept = Some(content.parse()?);
+            } else if name == "provides" {
+                let content;
+                syn::parenthesized!(content in input);
+                let provided = Punctuated::<ProvidedContract, Token![,]>::parse_terminated(&content)?;
+                attributes.provides.extend(provided);
+            } else {
+                return Err(syn::Error::new(
+                    name.span(),
+                    format!("Unknown context argument `{}`", name),
+                ));
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+impl Parse for ProvidedContract {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let contract: Path = input.parse()?;
+
+        let mut substitutions = Vec::new();
+        let mut predicates = Vec::new();
+        if input.parse::<Option<Token![where]>>()?.is_some() {
+            while !input.peek(syn::token::Brace) {
+                if input.peek(Ident) && input.peek2(Token![=]) {
+                    let generic: Ident = input.parse()?;
+                    input.parse::<Token![=]>()?;
+                    substitutions.push((generic, input.parse()?));
+                } else {
+                    predicates.push(input.parse()?);
+                }
+                if !input.peek(syn::token::Brace) {
+                    input.parse::<Token![,]>()?;
+                }
+            }
+        }
+
+        let content;
+        syn::braced!(content in input);
+        let mut methods = Vec::new();
+        while !content.is_empty() {
+            methods.push(content.parse()?);
+        }
+
+        Ok(ProvidedContract {
+            attrs,
+            contract,
+            substitutions,
+            predicates,
+            methods,
+        })
+    }
+}
+
+impl Parse for ProvidedMethod {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let signature: Signature = input.parse()?;
+        let (interaction, args) = if input.parse::<Option<Token![=>]>>()?.is_some() {
+            let interaction: Ident = input.parse()?;
+            let content;
+            syn::parenthesized!(content in input);
+            (interaction, Punctuated::parse_terminated(&content)?)
+        } else {
+            // Forward to the interaction of the same name with the arguments unchanged
+            let args = signature
+                .inputs
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::FnArg::Typed(pat_type) => {
+                        let pat = &pat_type.pat;
+                        Some::<Expr>(syn::parse_quote!(#pat))
+                    }
+                    syn::FnArg::Receiver(_) => None,
+                })
+                .collect();
+            (signature.ident.clone(), args)
+        };
+        input.parse::<Token![;]>()?;
+        Ok(ProvidedMethod {
+            signature,
+            interaction,
+            args,
+        })
+    }
+}
@@ -0,0 +1,146 @@
+use super::*;
+
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct ContextInfo {
+    pub name: syn::Ident,
+    pub generics: GenericsInfo,
+    pub properties: Vec<PropertyInfo>,
+    pub impl_blocks: Vec<ImplBlockInfo>,
+    pub roles: Vec<Role>,
+    pub attrs: Vec<syn::Attribute>,
+    pub async_strategy: AsyncStrategy,
+    /// The `five::Interceptor` run around every interaction, given with
+    /// `#[five::context(intercept(Type))]`
+    pub interceptor: Option<syn::Type>,
+    /// The path of the `five` crate in generated code, `crate = path` or `::five`
+    pub crate_path: syn::Path,
+}
+
+/// A role field of the context with the role played through it
+#[derive(Clone)]
+pub struct RoleField<'a> {
+    pub field: syn::Ident,
+    /// The name of the role, the last segment of the field's type
+    pub role_name: syn::Ident,
+    /// The role, `None` for a role defined outside the module
+    pub role: Option<&'a Role>,
+    /// The contracts the player must implement
+    pub contracts: Vec<String>,
+}
+
+/// How async interactions and role methods are declared by the generated traits
+#[derive(Clone)]
+pub enum AsyncStrategy {
+    /// `async fn`, whether the futures are `Send` depends on the players
+    Native,
+    /// `fn ... -> impl Future<Output = _> + Send`, chosen with `#[five::context(send)]`
+    Send,
+    /// Boxed `Send` futures, chosen with `send` when the contracts use `#[async_trait]`
+    AsyncTrait(Box<syn::Attribute>),
+}
+
+#[allow(dead_code)]
+impl ContextInfo {
+    pub fn new(
+        name: syn::Ident,
+        generics: GenericsInfo,
+        properties: Vec<PropertyInfo>,
+        impl_blocks: Vec<ImplBlockInfo>,
+        roles: Vec<Role>,
+        attrs: Vec<syn::Attribute>,
+    ) -> Self {
+        ContextInfo {
+            name,
+            generics,
+            properties,
+            impl_blocks,
+            roles,
+            attrs,
+            async_strategy: AsyncStrategy::Native,
+            interceptor: None,
+            crate_path: syn::parse_quote!(::five),
+        }
+    }
+
+    /// The role fields of the context, in the order they are declared
+    pub fn role_fields(&self) -> Vec<RoleField<'_>> {
+        self.properties
+            .iter()
+            .filter(|prop| prop.is_role())
+            .filter_map(|prop| {
+                let ty = prop.get_ty();
+                let syn::Type::Path(type_path) = &ty else {
+                    return None;
+                };
+                let role_name = type_path.path.segments.last()?.ident.clone();
+                let role = self.roles.iter().find(|role| role.name == role_name);
+                let contracts = match role {
+                    Some(role) => role.player_bounds().iter().map(compact).collect(),
+                    // A role defined outside the module is implemented by the players themselves
+                    None => vec![compact(&ty)],
+                };
+                Some(RoleField {
+                    field: prop.get_name(),
+                    role_name,
+                    role,
+                    contracts,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether `name` is an interaction written in the context, rather than a generated
+    /// method such as `with_<role>`
+    pub fn is_interaction(&self, name: &syn::Ident) -> bool {
+        self.impl_blocks
+            .iter()
+            .flat_map(|block| &block.functions)
+            .any(|f| f.get_name() == name)
+    }
+
+    /// Whether any interaction or role method is async
+    pub fn is_async(&self) -> bool {
+        let interactions = self.impl_blocks.iter().flat_map(|block| &block.functions);
+        let role_methods = self.roles.iter().flat_map(|role| &role.methods);
+        interactions.chain(role_methods).any(|f| f.get_asyncness().is_some())
+    }
+
+    /// Whether the futures of async interactions and role methods must be `Send`,
+    /// which requires the players to be `Send + Sync`
+    pub fn needs_send(&self) -> bool {
+        !matches!(self.async_strategy, AsyncStrategy::Native) && self.is_async()
+    }
+}
+
+pub fn analyze_context(
+    item_struct: &syn::ItemStruct,
+    impl_blocks: &[syn::ItemImpl],
+) -> ContextInfo {
+    
+    let generics = analyze_generics(&syn::Item::Struct(item_struct.clone()));
+    
+    let properties = item_struct
+        .fields
+        .iter()
+        .map(|field| PropertyInfo::new(field.ident.clone().unwrap(), field.ty.clone(), field.attrs.clone()))
+        .collect();
+
+    // Analyze the provided impl blocks directly
+    let analyzed_impl_blocks = impl_blocks
+        .iter()
+        .map(|block| analyze_context_impl_block(block, item_struct))
+        .collect();
+    
+    ContextInfo {
+        name: item_struct.ident.clone(),
+        generics,
+        properties,
+        impl_blocks: analyzed_impl_blocks,
+        roles: vec![],
+        attrs: item_struct.attrs.clone(),
+        async_strategy: AsyncStrategy::Native,
+        interceptor: None,
+        crate_path: syn::parse_quote!(::five),
+    }
+}
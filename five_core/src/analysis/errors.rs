@@ -1,4 +1,5 @@
 use proc_macro2::Span;
+use std::cell::RefCell;
 use std::fmt;
 use syn::Error as SynError;
 
@@ -99,4 +100,19 @@ pub fn create_multiple_errors<T: fmt::Display>(message: T, errors: Vec<MacroErro
     }
     
     MacroError::new(combined_message, span)
-} 
\ No newline at end of file
+}
+
+thread_local! {
+    static ERRORS: RefCell<Vec<MacroError>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Records an error found while compiling without interrupting the compilation, so all
+/// problems in a context are reported at once
+pub fn emit_error(error: MacroError) {
+    ERRORS.with(|errors| errors.borrow_mut().push(error));
+}
+
+/// Takes the errors recorded with `emit_error` since the last call
+pub fn take_errors() -> Vec<MacroError> {
+    ERRORS.with(|errors| errors.borrow_mut().drain(..).collect())
+}
@@ -95,6 +95,23 @@ impl FunctionDescription {
         }
     }
 
+    /// The signature as written in the source, without the body
+    pub fn signature(&self) -> String {
+        let asyncness = self.get_asyncness().map(|_| "async ").unwrap_or_default();
+        let params: Vec<String> = self.get_params().iter().map(compact).collect();
+        let generics = self.get_generics().get_params();
+        let generics = if generics.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", generics.iter().map(compact).collect::<Vec<_>>().join(", "))
+        };
+        let output = match self.get_output() {
+            ReturnType::Default => String::new(),
+            ReturnType::Type(_, ty) => format!(" -> {}", compact(ty)),
+        };
+        format!("{}fn {}{}({}){}", asyncness, self.get_name(), generics, params.join(", "), output)
+    }
+
     pub fn new_declaration(
         name: syn::Ident,
         params: Vec<ParameterInfo>,
@@ -153,7 +170,7 @@ pub fn analyze_trait_methods(item_trait: &syn::ItemTrait) -> Vec<FunctionDescrip
                         generics,
                         output,
                         body: body.clone(),
-                        asyncness: method.sig.asyncness.clone(),
+                        asyncness: method.sig.asyncness,
                         attrs: method.attrs.clone(),
                     })
                 } else {
@@ -162,7 +179,7 @@ pub fn analyze_trait_methods(item_trait: &syn::ItemTrait) -> Vec<FunctionDescrip
                         params,
                         generics,
                         output,
-                        asyncness: method.sig.asyncness.clone(),
+                        asyncness: method.sig.asyncness,
                         attrs: method.attrs.clone(),
                     })
                 }
@@ -172,3 +189,26 @@ pub fn analyze_trait_methods(item_trait: &syn::ItemTrait) -> Vec<FunctionDescrip
         })
         .collect()
 }
+
+/// Prints tokens without the spaces `TokenStream` puts between every token
+pub fn compact(tokens: &impl quote::ToTokens) -> String {
+    let mut text = tokens.to_token_stream().to_string();
+    for (spaced, tight) in [
+        (" :: ", "::"),
+        (":: ", "::"),
+        (" < ", "<"),
+        ("< ", "<"),
+        (" >", ">"),
+        (" ,", ","),
+        ("( ", "("),
+        (" )", ")"),
+        ("[ ", "["),
+        (" ]", "]"),
+        ("& ", "&"),
+        (" ;", ";"),
+        (" : ", ": "),
+    ] {
+        text = text.replace(spaced, tight);
+    }
+    text
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use syn::Ident;
+
+use super::errors::{emit_error, MacroError};
+use super::*;
+
+/// Prefix of the `PhantomData` fields added for generic parameters no field uses
+const PHANTOM_FIELD_PREFIX: &str = "__five_phantom_";
+
+/// The generic parameter standing in for the player of a role field, `ledger` -> `TLedger`
+pub fn role_generic_name(field_name: &Ident) -> Ident {
+    Ident::new(&format!("T{}", to_upper_camel_case(&field_name.to_string())), field_name.span())
+}
+
+/// The generic parameter of the new player taken by `with_<role>`. It's never named by the
+/// user, so it's prefixed to stay out of the way of their generics
+pub fn new_role_generic_name(field_name: &Ident) -> Ident {
+    Ident::new(&format!("__TNew{}", to_upper_camel_case(&field_name.to_string())), field_name.span())
+}
+
+/// `with_<role>`, recasting a role with a player of another type
+pub fn with_role_name(field_name: &Ident) -> Ident {
+    Ident::new(&format!("with_{}", field_name), field_name.span())
+}
+
+/// `replace_<role>`, swapping in a player of the same type
+pub fn replace_role_name(field_name: &Ident) -> Ident {
+    Ident::new(&format!("replace_{}", field_name), field_name.span())
+}
+
+/// The context trait, named after the module in PascalCase
+pub fn context_trait_name(module_name: &Ident) -> Ident {
+    Ident::new(&to_upper_camel_case(&module_name.to_string()), module_name.span())
+}
+
+/// The function binding the players and data of a context
+pub fn bind_name(module_name: &Ident) -> Ident {
+    Ident::new("bind", module_name.span())
+}
+
+/// The constant describing the roles, data and interactions of a context
+pub fn descriptor_name(module_name: &Ident) -> Ident {
+    Ident::new("DESCRIPTOR", module_name.span())
+}
+
+/// The mock generated for a contract by `#[five::mock]`, `LedgerContract` -> `MockLedgerContract`
+pub fn mock_name(contract_name: &Ident) -> Ident {
+    Ident::new(&format!("Mock{}", contract_name), contract_name.span())
+}
+
+/// The player recording the calls of a contract, generated by `#[five::record]`
+pub fn recording_name(contract_name: &Ident) -> Ident {
+    Ident::new(&format!("Recording{}", contract_name), contract_name.span())
+}
+
+/// The player replaying the recorded calls of a contract, generated by `#[five::record]`
+pub fn replaying_name(contract_name: &Ident) -> Ident {
+    Ident::new(&format!("Replaying{}", contract_name), contract_name.span())
+}
+
+/// The `PhantomData` field for an otherwise unused generic parameter of the context
+pub fn phantom_field_name(param: &Ident) -> Ident {
+    Ident::new(&format!("{}{}", PHANTOM_FIELD_PREFIX, param), proc_macro2::Span::mixed_site())
+}
+
+pub fn is_phantom_field(field_name: &Ident) -> bool {
+    field_name.to_string().starts_with(PHANTOM_FIELD_PREFIX)
+}
+
+pub(crate) fn to_upper_camel_case(input: &str) -> String {
+    input
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<String>()
+}
+
+/// Reports names of the module that collide with the names of generated items.
+///
+/// The generated items are emitted next to the user's items in the context module and
+/// the generated generic parameters next to the user's, so a collision would otherwise
+/// surface as a confusing error in code the user never wrote. Names that are only used by
+/// the generated code are prefixed or hygienic instead.
+pub fn check_generated_names(module_name: &Ident, items: &[syn::Item], context: &ContextInfo) {
+    // Generic parameters added to the context struct and its methods, by the role field
+    // they are generated for
+    let mut generated_generics: HashMap<String, (Ident, &str)> = HashMap::new();
+    let mut generated_methods: HashMap<String, Ident> = HashMap::new();
+    for prop in context.properties.iter().filter(|prop| prop.is_role()) {
+        let field = prop.get_name();
+        generated_generics.insert(role_generic_name(&field).to_string(), (field.clone(), "the player"));
+        generated_generics.insert(new_role_generic_name(&field).to_string(), (field.clone(), "the new player"));
+        generated_methods.insert(with_role_name(&field).to_string(), field.clone());
+        generated_methods.insert(replace_role_name(&field).to_string(), field);
+    }
+
+    let role_methods = context.roles.iter().flat_map(|role| &role.methods);
+    let interactions = context.impl_blocks.iter().flat_map(|block| &block.functions);
+    let generics = context
+        .generics
+        .get_params()
+        .into_iter()
+        .chain(role_methods.chain(interactions.clone()).flat_map(|f| f.get_generics().get_params()));
+    for param in generics {
+        if let syn::GenericParam::Type(param) = param {
+            if let Some((field, player)) = generated_generics.get(&param.ident.to_string()) {
+                emit_error(MacroError::new(
+                    format!(
+                        "`{}` is the generic parameter generated for {} of the role `{}`, rename this parameter",
+                        param.ident, player, field
+                    ),
+                    param.ident.span(),
+                ));
+            }
+        }
+    }
+
+    for function in interactions {
+        let name = function.get_name();
+        if let Some(field) = generated_methods.get(&name.to_string()) {
+            emit_error(MacroError::new(
+                format!("`{}` is generated for recasting the role `{}`, rename this interaction", name, field),
+                name.span(),
+            ));
+        }
+    }
+
+    for prop in &context.properties {
+        if is_phantom_field(&prop.get_name()) {
+            emit_error(MacroError::new(
+                format!("field names starting with `{}` are reserved for generated fields", PHANTOM_FIELD_PREFIX),
+                prop.get_name().span(),
+            ));
+        }
+    }
+
+    // Generated items end up in the context module next to the user's items
+    let generated_items = [context_trait_name(module_name), bind_name(module_name), descriptor_name(module_name)];
+    for item in items {
+        for ident in item_names(item) {
+            if let Some(generated) = generated_items.iter().find(|generated| *generated == ident) {
+                emit_error(MacroError::new(
+                    format!("`{}` is generated for the context `{}`, rename this item", generated, module_name),
+                    ident.span(),
+                ));
+            }
+        }
+    }
+}
+
+/// The names an item brings into scope
+fn item_names(item: &syn::Item) -> Vec<&Ident> {
+    fn use_names<'a>(tree: &'a syn::UseTree, names: &mut Vec<&'a Ident>) {
+        match tree {
+            syn::UseTree::Path(path) => use_names(&path.tree, names),
+            syn::UseTree::Name(name) => names.push(&name.ident),
+            syn::UseTree::Rename(rename) => names.push(&rename.rename),
+            syn::UseTree::Group(group) => group.items.iter().for_each(|tree| use_names(tree, names)),
+            syn::UseTree::Glob(_) => (),
+        }
+    }
+
+    match item {
+        syn::Item::Const(item) => vec![&item.ident],
+        syn::Item::Enum(item) => vec![&item.ident],
+        syn::Item::Fn(item) => vec![&item.sig.ident],
+        syn::Item::Mod(item) => vec![&item.ident],
+        syn::Item::Static(item) => vec![&item.ident],
+        syn::Item::Struct(item) => vec![&item.ident],
+        syn::Item::Trait(item) => vec![&item.ident],
+        syn::Item::TraitAlias(item) => vec![&item.ident],
+        syn::Item::Type(item) => vec![&item.ident],
+        syn::Item::Union(item) => vec![&item.ident],
+        syn::Item::Use(item) => {
+            let mut names = vec![];
+            use_names(&item.tree, &mut names);
+            names
+        }
+        syn::Item::Macro(item) => item.ident.iter().collect(),
+        _ => vec![],
+    }
+}
@@ -20,6 +20,12 @@ impl GenericsInfo {
     pub fn get_where_clause(&self) -> Option<WhereClause> {
         self.where_clause.clone()
     }
+    /// Adds `predicates` to the where clause
+    pub fn add_predicates(&mut self, predicates: Vec<syn::WherePredicate>) {
+        if !predicates.is_empty() {
+            self.where_clause = merge_where_clause(self.where_clause.take(), predicates);
+        }
+    }
 }
 
 impl ToTokens for GenericsInfo {
@@ -125,10 +131,10 @@ fn extract_inline_where_clauses(item: &syn::Item) -> (Option<&Generics>, Vec<syn
             Some(&item_trait.generics),
             extract_inline_where_clauses_from_trait(item_trait),
         ),
-        syn::Item::Struct(item_struct) => (
-            Some(&item_struct.generics),
-            extract_inline_where_clauses_from_struct(item_struct),
-        ),
+        // Struct bounds are kept inline on the parameters and the where clause is used as-is.
+        // Copying the inline bounds into the where clause as well bounded the parameters
+        // twice, which clippy reports on the user's struct, and repeated the where clause.
+        syn::Item::Struct(item_struct) => (Some(&item_struct.generics), Vec::new()),
         _ => (None, Vec::new()),
     }
 }
@@ -212,36 +218,3 @@ pub fn extract_inline_where_clauses_from_signature(
         })
         .collect()
 }
-
-fn extract_inline_where_clauses_from_struct(
-    item_struct: &syn::ItemStruct,
-) -> Vec<syn::WherePredicate> {
-    // Extract predicates from generic parameters similar to traits
-    let mut where_clauses: Vec<syn::WherePredicate> = item_struct
-        .generics
-        .params
-        .iter()
-        .filter_map(|param| {
-            if let syn::GenericParam::Type(type_param) = param {
-                Some(syn::WherePredicate::Type(syn::PredicateType {
-                    lifetimes: None,
-                    bounded_ty: syn::Type::Path(syn::TypePath {
-                        qself: None,
-                        path: type_param.ident.clone().into(),
-                    }),
-                    colon_token: Default::default(),
-                    bounds: type_param.bounds.clone(),
-                }))
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    // Add any existing where clause predicates
-    if let Some(where_clause) = &item_struct.generics.where_clause {
-        where_clauses.extend(where_clause.predicates.clone());
-    }
-
-    where_clauses
-}
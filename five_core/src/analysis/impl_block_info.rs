@@ -0,0 +1,247 @@
+use std::collections::HashSet;
+
+use quote::ToTokens;
+use syn::spanned::Spanned;
+use syn::{GenericArgument, GenericParam, Item, PathArguments, Type};
+
+use super::errors::{emit_error, MacroError};
+use super::*;
+
+#[derive(Clone)]
+pub struct ImplBlockInfo {
+    pub generics: GenericsInfo,
+    pub for_lifetimes: Option<syn::Lifetime>,
+    pub implemented_traits: Vec<syn::Path>,
+    pub functions: Vec<FunctionDescription>,
+    pub self_ty: Type,
+    pub attrs: Vec<syn::Attribute>,
+}
+impl ImplBlockInfo {
+    pub fn new(
+        generics: GenericsInfo,
+        for_lifetimes: Option<syn::Lifetime>,
+        implemented_traits: Vec<syn::Path>,
+        functions: Vec<FunctionDescription>,
+        self_ty: Type,
+        attrs: Vec<syn::Attribute>,
+    ) -> Self {
+        ImplBlockInfo {
+            generics,
+            for_lifetimes,
+            implemented_traits,
+            functions,
+            self_ty,
+            attrs,
+        }
+    }
+}
+pub fn analyze_impl_block(item_impl: &syn::ItemImpl) -> ImplBlockInfo {
+    // Analyze generics
+    let generics = analyze_generics(&Item::Impl(item_impl.clone()));
+    let self_ty = (*item_impl.self_ty).clone();
+    // Analyze for<> lifetimes
+    let for_lifetimes = item_impl.generics.params.iter().find_map(|param| {
+        if let syn::GenericParam::Lifetime(lifetime_def) = param {
+            Some(lifetime_def.lifetime.clone())
+        } else {
+            None
+        }
+    });
+
+    // Analyze implemented traits
+    let implemented_traits = item_impl
+        .trait_
+        .as_ref()
+        .map_or(Vec::new(), |(_, path, _)| vec![path.clone()]);
+
+    // Analyze methods in the impl block
+    let functions = item_impl
+        .items
+        .iter()
+        .filter_map(|item| {
+            if let syn::ImplItem::Fn(method) = item {
+                let params = analyze_parameters(&method.sig);
+                let generics = analyze_generics_from_impl_method(method);
+                let body = method.block.clone();
+
+                Some(FunctionDescription::Implementation {
+                    name: method.sig.ident.clone(),
+                    params,
+                    generics,
+                    output: method.sig.output.clone(),
+                    body,
+                    asyncness: method.sig.asyncness,
+                    attrs: method.attrs.clone(),
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    // Create and return the ImplBlockInfo
+    ImplBlockInfo::new(
+        generics,
+        for_lifetimes,
+        implemented_traits,
+        functions,
+        self_ty,
+        item_impl.attrs.clone(),
+    )
+}
+
+/// Analyzes an impl block of the context struct.
+///
+/// The interactions of all impl blocks make up one context trait, so the blocks must be
+/// generic over the parameters of the struct. Bounds the struct doesn't have are moved to
+/// the where clauses of the block's functions, which are then only available to
+/// instantiations meeting them.
+pub fn analyze_context_impl_block(item_impl: &syn::ItemImpl, context: &syn::ItemStruct) -> ImplBlockInfo {
+    check_impl_generics(item_impl, context);
+    let conditions = impl_conditions(item_impl, context);
+    let mut block = analyze_impl_block(item_impl);
+    for function in &mut block.functions {
+        if let FunctionDescription::Implementation { generics, .. } = function {
+            generics.add_predicates(conditions.clone());
+        }
+    }
+    block
+}
+
+/// Reports impl generics that don't line up with the generics of the context struct
+fn check_impl_generics(item_impl: &syn::ItemImpl, context: &syn::ItemStruct) {
+    let args: Vec<&GenericArgument> = match &*item_impl.self_ty {
+        Type::Path(type_path) => match type_path.path.segments.last().map(|segment| &segment.arguments) {
+            Some(PathArguments::AngleBracketed(args)) => args.args.iter().collect(),
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+    let params: Vec<&GenericParam> = context.generics.params.iter().collect();
+    if args.len() != params.len() {
+        emit_error(MacroError::new(
+            format!(
+                "the context has {} generic parameter(s) but the impl block gives {}",
+                params.len(),
+                args.len()
+            ),
+            item_impl.self_ty.span(),
+        ));
+        return;
+    }
+
+    let declared: Vec<String> = item_impl.generics.params.iter().map(param_name).collect();
+    let mut used = HashSet::new();
+    for (arg, param) in args.into_iter().zip(params) {
+        let expected = param_name(param);
+        let name = match arg {
+            GenericArgument::Type(Type::Path(path)) if path.qself.is_none() => {
+                path.path.get_ident().map(ToString::to_string)
+            }
+            GenericArgument::Lifetime(lifetime) => Some(lifetime.to_string()),
+            _ => None,
+        };
+        match name {
+            Some(name) if declared.contains(&name) => {
+                if name != expected {
+                    emit_error(MacroError::new(
+                        format!("`{}` must be named `{}` as in the context struct", name, expected),
+                        arg.span(),
+                    ));
+                }
+                used.insert(name);
+            }
+            Some(name) if name == expected => emit_error(MacroError::new(
+                format!("`{}` must be declared by the impl block, as in `impl<{}> ...`", name, name),
+                arg.span(),
+            )),
+            _ => emit_error(MacroError::new(
+                format!(
+                    "impl blocks of the context can't be specialised, `{}` fixes `{}`; \
+                     declare `{}` on the impl block and bound it in a where clause instead",
+                    arg.to_token_stream(),
+                    expected,
+                    expected
+                ),
+                arg.span(),
+            )),
+        }
+    }
+    for param in &item_impl.generics.params {
+        if !used.contains(&param_name(param)) {
+            emit_error(MacroError::new(
+                format!("`{}` is not a generic parameter of the context", param_name(param)),
+                param.span(),
+            ));
+        }
+    }
+}
+
+/// The bounds of the impl block that the context struct doesn't have, one predicate per bound
+fn impl_conditions(item_impl: &syn::ItemImpl, context: &syn::ItemStruct) -> Vec<syn::WherePredicate> {
+    let key = |predicate: &syn::WherePredicate| predicate.to_token_stream().to_string();
+    let struct_bounds: HashSet<String> = split_bounds(&context.generics).iter().map(key).collect();
+    split_bounds(&item_impl.generics)
+        .into_iter()
+        .filter(|predicate| !struct_bounds.contains(&key(predicate)))
+        .collect()
+}
+
+/// The type and lifetime bounds of `generics`, inline or in the where clause, split into one
+/// predicate per bound
+fn split_bounds(generics: &syn::Generics) -> Vec<syn::WherePredicate> {
+    let inline_types = generics.type_params().map(|param| {
+        let ident = &param.ident;
+        syn::WherePredicate::Type(syn::PredicateType {
+            lifetimes: None,
+            bounded_ty: syn::parse_quote!(#ident),
+            colon_token: Default::default(),
+            bounds: param.bounds.clone(),
+        })
+    });
+    let inline_lifetimes = generics.lifetimes().map(|param| {
+        syn::WherePredicate::Lifetime(syn::PredicateLifetime {
+            lifetime: param.lifetime.clone(),
+            colon_token: Default::default(),
+            bounds: param.bounds.clone(),
+        })
+    });
+    let where_clause = generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| where_clause.predicates.iter().cloned());
+    inline_lifetimes
+        .chain(inline_types)
+        .chain(where_clause)
+        .flat_map(|predicate| match predicate {
+            syn::WherePredicate::Type(predicate) => predicate
+                .bounds
+                .iter()
+                .map(|bound| {
+                    syn::WherePredicate::Type(syn::PredicateType {
+                        bounds: std::iter::once(bound.clone()).collect(),
+                        ..predicate.clone()
+                    })
+                })
+                .collect::<Vec<_>>(),
+            syn::WherePredicate::Lifetime(predicate) => predicate
+                .bounds
+                .iter()
+                .map(|bound| {
+                    syn::WherePredicate::Lifetime(syn::PredicateLifetime {
+                        bounds: std::iter::once(bound.clone()).collect(),
+                        ..predicate.clone()
+                    })
+                })
+                .collect(),
+            predicate => vec![predicate],
+        })
+        .collect()
+}
+
+fn param_name(param: &GenericParam) -> String {
+    match param {
+        GenericParam::Type(param) => param.ident.to_string(),
+        GenericParam::Lifetime(param) => param.lifetime.to_string(),
+        GenericParam::Const(param) => param.ident.to_string(),
+    }
+}
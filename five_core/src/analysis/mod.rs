@@ -1,21 +1,31 @@
+mod call_graph;
 mod code_analysis;
+mod context_attributes;
 mod context_info;
+pub mod errors;
 mod function_description;
+mod generated_names;
 mod generics_info;
 mod impl_block_info;
 mod parameter_info;
+pub mod players;
 mod property_info;
 mod role;
+mod role_borrows;
 mod trait_info;
 mod type_description;
 
+pub use call_graph::*;
 pub use code_analysis::*;
+pub use context_attributes::*;
 pub use context_info::*;
 pub use function_description::*;
+pub use generated_names::*;
 pub use generics_info::*;
 pub use impl_block_info::*;
 pub use parameter_info::*;
 pub use property_info::*;
 pub use role::*;
+pub use role_borrows::*;
 pub use trait_info::*;
 pub use type_description::*;
@@ -1,10 +1,14 @@
 use quote::ToTokens;
+use syn::spanned::Spanned;
 use syn::{Ident, Lifetime, Type};
 
-#[allow(dead_code)]
+use super::errors::{emit_error, MacroError};
+
+#[allow(dead_code, clippy::large_enum_variant)]
 #[derive(Clone)]
 pub enum ParameterInfo {
     SelfRef,                                // Represents `self`, `&self`, or `&mut self`
+    MutSelf,                                // `mut self`, taken by value
     ImmutableReference(Box<ParameterInfo>), // &T or &self
     MutableReference(Box<ParameterInfo>),   // &mut T or &mut self
     LifeTime(Lifetime, Box<ParameterInfo>), // Lifetime annotations
@@ -19,26 +23,26 @@ impl ParameterInfo {
         match self {
             ParameterInfo::ImmutableReference(pi) => pi.inner(),
             ParameterInfo::MutableReference(pi) => pi.inner(),
-            ParameterInfo::SelfRef => self,
+            ParameterInfo::SelfRef | ParameterInfo::MutSelf => self,
             ParameterInfo::Typed { name: _, ty: _ } => self,
             ParameterInfo::LifeTime(..) => panic!("Recursive Lifetime should not happen"),
         }
     }
     pub fn name(&self) -> String {
         match self.inner() {
-            ParameterInfo::SelfRef => "self".to_string(),
+            ParameterInfo::SelfRef | ParameterInfo::MutSelf => "self".to_string(),
             ParameterInfo::Typed { name, ty: _ } => name.to_string(),
             _ => panic!("Should have been removed in inner()"),
         }
     }
 
     pub fn is_self(&self) -> bool {
-        matches!(self.inner(), ParameterInfo::SelfRef)
+        matches!(self.inner(), ParameterInfo::SelfRef | ParameterInfo::MutSelf)
     }
 
     pub fn get_self_type(&self) -> SelfType {
         match self {
-            ParameterInfo::SelfRef => SelfType::Value,
+            ParameterInfo::SelfRef | ParameterInfo::MutSelf => SelfType::Value,
             ParameterInfo::ImmutableReference(inner)
                 if matches!(**inner, ParameterInfo::SelfRef) =>
             {
@@ -51,23 +55,32 @@ impl ParameterInfo {
         }
     }
 
-    pub fn new_owned(name: &str, ty: syn::Type) -> Self {
+    /// The parameter as written in a declaration without a body, where `mut self` is
+    /// not allowed
+    pub fn declared(&self) -> &ParameterInfo {
+        match self {
+            ParameterInfo::MutSelf => &ParameterInfo::SelfRef,
+            _ => self,
+        }
+    }
+
+    pub fn new_owned(name: syn::Ident, ty: syn::Type) -> Self {
         ParameterInfo::Typed {
-            name: syn::Ident::new(name, proc_macro2::Span::call_site()),
+            name,
             ty,
         }
     }
 
-    pub fn new_ref(name: &str, ty: syn::Type) -> Self {
+    pub fn new_ref(name: syn::Ident, ty: syn::Type) -> Self {
         ParameterInfo::ImmutableReference(Box::new(ParameterInfo::Typed {
-            name: syn::Ident::new(name, proc_macro2::Span::call_site()),
+            name,
             ty,
         }))
     }
 
-    pub fn new_mut_ref(name: &str, ty: syn::Type) -> Self {
+    pub fn new_mut_ref(name: syn::Ident, ty: syn::Type) -> Self {
         ParameterInfo::MutableReference(Box::new(ParameterInfo::Typed {
-            name: syn::Ident::new(name, proc_macro2::Span::call_site()),
+            name,
             ty,
         }))
     }
@@ -77,16 +90,29 @@ pub fn analyze_parameters(sig: &syn::Signature) -> Vec<ParameterInfo> {
     sig.inputs
         .iter()
         .filter_map(|arg| match arg {
-            // Handle `self`, `&self`, and `&mut self`
+            // Handle `self`, `mut self`, `&self`, and `&mut self`, also when written with
+            // their type, e.g. `self: &mut Self`
             syn::FnArg::Receiver(receiver) => {
-                let kind = if receiver.reference.is_some() {
-                    if receiver.mutability.is_some() {
-                        ParameterInfo::MutableReference(Box::new(ParameterInfo::SelfRef))
-                    } else {
-                        ParameterInfo::ImmutableReference(Box::new(ParameterInfo::SelfRef))
+                let kind = match &*receiver.ty {
+                    _ if receiver.colon_token.is_none() => {
+                        receiver_kind(receiver.reference.is_some(), receiver.mutability.is_some())
+                    }
+                    Type::Path(path) if path.qself.is_none() && path.path.is_ident("Self") => {
+                        receiver_kind(false, receiver.mutability.is_some())
+                    }
+                    Type::Reference(reference)
+                        if matches!(&*reference.elem, Type::Path(path) if path.path.is_ident("Self")) =>
+                    {
+                        receiver_kind(true, reference.mutability.is_some())
+                    }
+                    ty => {
+                        emit_error(MacroError::new(
+                            "only `self`, `&self` and `&mut self` receivers are supported, take the \
+                             context by value or by reference",
+                            ty.span(),
+                        ));
+                        return None;
                     }
-                } else {
-                    panic!("Self should be passed by reference or not be present")
                 };
                 Some(kind)
             }
@@ -116,8 +142,17 @@ pub fn analyze_parameters(sig: &syn::Signature) -> Vec<ParameterInfo> {
         })
         .collect()
 }
+fn receiver_kind(reference: bool, mutable: bool) -> ParameterInfo {
+    match (reference, mutable) {
+        (true, true) => ParameterInfo::MutableReference(Box::new(ParameterInfo::SelfRef)),
+        (true, false) => ParameterInfo::ImmutableReference(Box::new(ParameterInfo::SelfRef)),
+        (false, true) => ParameterInfo::MutSelf,
+        (false, false) => ParameterInfo::SelfRef,
+    }
+}
+
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SelfType {
     Value,
     Reference,
@@ -128,6 +163,7 @@ impl ToTokens for ParameterInfo {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
         match self {
             ParameterInfo::SelfRef => tokens.extend(quote::quote!(self)),
+            ParameterInfo::MutSelf => tokens.extend(quote::quote!(mut self)),
             ParameterInfo::ImmutableReference(inner) => {
                 tokens.extend(quote::quote!(&));
                 inner.to_tokens(tokens);
@@ -0,0 +1,247 @@
+use std::collections::{HashMap, HashSet};
+
+use syn::visit_mut::VisitMut;
+use syn::{Expr, Member, Pat};
+
+use super::is_self_context;
+
+/// What an expression in an interaction or role method refers to, when it is the player of
+/// a role
+#[derive(Clone, Debug, PartialEq)]
+pub enum Player {
+    /// The role field itself, `self.<field>` in interactions, `self.context.<field>` or
+    /// `self` in role methods
+    Field(String),
+    /// A local variable bound to the role field, `let ledger = &mut self.ledger;`, and
+    /// whether it holds a reference to the field rather than the field itself
+    Alias {
+        field: String,
+        name: syn::Ident,
+        by_ref: bool,
+    },
+}
+
+impl Player {
+    /// The role field the player is in
+    pub fn field(&self) -> &str {
+        match self {
+            Player::Field(field) | Player::Alias { field, .. } => field,
+        }
+    }
+}
+
+/// Classifies the receivers of calls in the body of an interaction or role method.
+///
+/// Local variables bound to a role field alias it until the end of their block, or until a
+/// `let`, closure parameter, `for` loop, `match` arm, `if let` or `while let` binds the same
+/// name. Visitors that rewrite or collect role calls track the scopes through [`VisitPlayers`].
+pub struct Players {
+    role_fields: HashSet<String>,
+    // The role field of the role method being visited, `None` in interactions
+    own_field: Option<String>,
+    // The variables bound in each enclosing scope that alias a role field, with that field
+    // and whether they hold a reference, or `None` for variables shadowing an alias
+    scopes: Vec<HashMap<String, Option<(String, bool)>>>,
+}
+
+impl Players {
+    /// The players of `role_fields`, in an interaction or, with `own_field`, in a method of
+    /// the role played through `own_field`
+    pub fn new(role_fields: impl IntoIterator<Item = String>, own_field: Option<String>) -> Self {
+        Players {
+            role_fields: role_fields.into_iter().collect(),
+            own_field,
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    /// The player `expr` refers to, possibly behind `&`, `&mut`, `*` or parentheses
+    pub fn player(&self, expr: &Expr) -> Option<Player> {
+        match expr {
+            Expr::Reference(reference) => self.player(&reference.expr),
+            Expr::Unary(unary) if matches!(unary.op, syn::UnOp::Deref(_)) => self.player(&unary.expr),
+            Expr::Paren(paren) => self.player(&paren.expr),
+            Expr::Path(path) if path.path.is_ident("self") => self.own_field.clone().map(Player::Field),
+            Expr::Path(path) => {
+                let name = path.path.get_ident()?;
+                let (field, by_ref) = self.alias(&name.to_string())?;
+                Some(Player::Alias {
+                    field: field.clone(),
+                    name: name.clone(),
+                    by_ref: *by_ref,
+                })
+            }
+            Expr::Field(field_expr) => {
+                let Member::Named(field) = &field_expr.member else {
+                    return None;
+                };
+                let field = field.to_string();
+                (self.is_context(&field_expr.base) && self.role_fields.contains(&field)).then_some(Player::Field(field))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `expr` is the context: `self` in interactions, `self.context` in role methods
+    pub fn is_context(&self, expr: &Expr) -> bool {
+        match (expr, &self.own_field) {
+            (Expr::Path(path), None) => path.path.is_ident("self"),
+            (Expr::Field(field_expr), Some(_)) => is_self_context(field_expr),
+            _ => false,
+        }
+    }
+
+    fn alias(&self, name: &str) -> Option<&(String, bool)> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name))
+            .and_then(Option::as_ref)
+    }
+
+    /// Starts a scope in which the variables bound by `pats` shadow any alias
+    fn enter<'p>(&mut self, pats: impl IntoIterator<Item = &'p Pat>) {
+        self.scopes.push(HashMap::new());
+        for pat in pats {
+            self.shadow(pat);
+        }
+    }
+
+    fn exit(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn shadow(&mut self, pat: &Pat) {
+        let mut names = vec![];
+        bound_names(pat, &mut names);
+        let scope = self.scopes.last_mut().unwrap();
+        for name in names {
+            scope.insert(name, None);
+        }
+    }
+
+    /// Tracks `let l = &mut self.ledger;`, any other binding shadows an alias of the same name
+    fn bind(&mut self, local: &syn::Local) {
+        let name = match &local.pat {
+            Pat::Ident(pat) if pat.subpat.is_none() => &pat.ident,
+            Pat::Type(pat) => match &*pat.pat {
+                Pat::Ident(pat) if pat.subpat.is_none() => &pat.ident,
+                _ => return self.shadow(&local.pat),
+            },
+            _ => return self.shadow(&local.pat),
+        };
+        let alias = local.init.as_ref().filter(|init| init.diverge.is_none()).and_then(|init| {
+            let mut expr = &*init.expr;
+            while let Expr::Paren(paren) = expr {
+                expr = &paren.expr;
+            }
+            match expr {
+                Expr::Reference(_) | Expr::Field(_) => self
+                    .player(expr)
+                    .map(|player| (player.field().to_string(), matches!(expr, Expr::Reference(_)))),
+                _ => None,
+            }
+        });
+        self.scopes.last_mut().unwrap().insert(name.to_string(), alias);
+    }
+}
+
+/// The names of the variables bound by `pat`
+fn bound_names(pat: &Pat, names: &mut Vec<String>) {
+    match pat {
+        Pat::Ident(pat) => {
+            names.push(pat.ident.to_string());
+            if let Some((_, subpat)) = &pat.subpat {
+                bound_names(subpat, names);
+            }
+        }
+        Pat::Or(pat) => pat.cases.iter().for_each(|case| bound_names(case, names)),
+        Pat::Paren(pat) => bound_names(&pat.pat, names),
+        Pat::Reference(pat) => bound_names(&pat.pat, names),
+        Pat::Slice(pat) => pat.elems.iter().for_each(|elem| bound_names(elem, names)),
+        Pat::Struct(pat) => pat.fields.iter().for_each(|field| bound_names(&field.pat, names)),
+        Pat::Tuple(pat) => pat.elems.iter().for_each(|elem| bound_names(elem, names)),
+        Pat::TupleStruct(pat) => pat.elems.iter().for_each(|elem| bound_names(elem, names)),
+        Pat::Type(pat) => bound_names(&pat.pat, names),
+        _ => (),
+    }
+}
+
+/// The patterns of the `let` expressions in the condition of an `if` or `while`
+fn let_patterns(cond: &Expr) -> Vec<&Pat> {
+    match cond {
+        Expr::Let(expr_let) => vec![&expr_let.pat],
+        Expr::Binary(binary) if matches!(binary.op, syn::BinOp::And(_)) => {
+            let mut pats = let_patterns(&binary.left);
+            pats.extend(let_patterns(&binary.right));
+            pats
+        }
+        Expr::Paren(paren) => let_patterns(&paren.expr),
+        _ => vec![],
+    }
+}
+
+/// A visitor of the body of an interaction or role method that classifies the receivers of
+/// calls with [`Players`]. Its `visit_block_mut`, `visit_local_mut` and `visit_expr_mut`
+/// call the functions of the same name below to keep the scopes of the aliases.
+pub trait VisitPlayers: VisitMut {
+    fn players(&mut self) -> &mut Players;
+}
+
+/// Visits `block` in a scope of its own
+pub fn visit_block_mut<V: VisitPlayers + ?Sized>(visitor: &mut V, block: &mut syn::Block) {
+    visitor.players().enter([]);
+    syn::visit_mut::visit_block_mut(visitor, block);
+    visitor.players().exit();
+}
+
+/// Visits `local`, then tracks whether the variable it binds aliases a role field
+pub fn visit_local_mut<V: VisitPlayers + ?Sized>(visitor: &mut V, local: &mut syn::Local) {
+    syn::visit_mut::visit_local_mut(visitor, local);
+    visitor.players().bind(local);
+}
+
+/// Visits the subexpressions of `expr`, with the variables bound by closure parameters,
+/// `for` loops, `match` arms, `if let` and `while let` in scope where they shadow aliases
+pub fn visit_expr_mut<V: VisitPlayers + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Closure(closure) => {
+            visitor.players().enter(&closure.inputs);
+            visitor.visit_expr_mut(&mut closure.body);
+            visitor.players().exit();
+        }
+        Expr::ForLoop(for_loop) => {
+            visitor.visit_expr_mut(&mut for_loop.expr);
+            visitor.players().enter([&*for_loop.pat]);
+            visitor.visit_block_mut(&mut for_loop.body);
+            visitor.players().exit();
+        }
+        Expr::Match(expr_match) => {
+            visitor.visit_expr_mut(&mut expr_match.expr);
+            for arm in &mut expr_match.arms {
+                visitor.players().enter([&arm.pat]);
+                if let Some((_, guard)) = &mut arm.guard {
+                    visitor.visit_expr_mut(guard);
+                }
+                visitor.visit_expr_mut(&mut arm.body);
+                visitor.players().exit();
+            }
+        }
+        Expr::If(expr_if) => {
+            visitor.visit_expr_mut(&mut expr_if.cond);
+            visitor.players().enter(let_patterns(&expr_if.cond));
+            visitor.visit_block_mut(&mut expr_if.then_branch);
+            visitor.players().exit();
+            if let Some((_, else_branch)) = &mut expr_if.else_branch {
+                visitor.visit_expr_mut(else_branch);
+            }
+        }
+        Expr::While(expr_while) => {
+            visitor.visit_expr_mut(&mut expr_while.cond);
+            visitor.players().enter(let_patterns(&expr_while.cond));
+            visitor.visit_block_mut(&mut expr_while.body);
+            visitor.players().exit();
+        }
+        _ => syn::visit_mut::visit_expr_mut(visitor, expr),
+    }
+}
@@ -0,0 +1,37 @@
+use syn::{Ident, Type};
+
+#[derive(Clone)]
+pub struct PropertyInfo {
+    name: Ident,
+    ty: Type,
+    attrs: Vec<syn::Attribute>,
+}
+impl PropertyInfo {
+    pub fn new(name: Ident, ty: Type, attrs: Vec<syn::Attribute>) -> Self {
+        PropertyInfo { name, ty, attrs }
+    }
+
+    pub fn get_name(&self) -> Ident {
+        self.name.clone()
+    }
+
+    pub fn get_ty(&self) -> Type {
+        self.ty.clone()
+    }
+
+    pub fn get_attrs(&self) -> &[syn::Attribute] {
+        &self.attrs
+    }
+
+    /// Whether the property is played by a role, i.e. its type is a `...Role` trait
+    pub fn is_role(&self) -> bool {
+        match &self.ty {
+            Type::Path(type_path) if type_path.qself.is_none() => type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident.to_string().ends_with("Role")),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use super::errors::{emit_error, MacroError};
+use super::*;
+
+#[derive(Clone)]
+pub struct Role {
+    pub name: syn::Ident,                  // The role's name (e.g., `SourceRole`)
+    pub contract: Option<TraitInfo>,       // Associated contract (e.g., `SourceContract`), if any
+    pub external_contract: Option<syn::Path>, // Contract defined outside the context module, if any
+    pub supertraits: syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]>, // Supertraits of the role trait
+    pub supertrait_contracts: Vec<TraitInfo>, // Contracts of the module among the supertraits, e.g. `AccountContract`
+    pub methods: Vec<FunctionDescription>, // Methods specific to the role
+    pub borrows: HashMap<String, RoleMethodBorrows>, // What each method borrows from the context
+}
+
+impl Role {
+    /// The bounds a player must satisfy to play this role: the supertraits of the role
+    /// and the matching contract, if the role doesn't already list it as a supertrait
+    pub fn player_bounds(&self) -> syn::punctuated::Punctuated<syn::TypeParamBound, syn::Token![+]> {
+        let mut bounds = self.supertraits.clone();
+        let contract: Option<syn::Path> = match (&self.contract, &self.external_contract) {
+            (_, Some(path)) => Some(path.clone()),
+            (Some(contract), None) => Some(contract.name.clone().into()),
+            (None, None) => None,
+        };
+        if let Some(contract) = contract {
+            let contract_name = &contract.segments.last().unwrap().ident;
+            let listed = self.supertraits.iter().any(|bound| match bound {
+                syn::TypeParamBound::Trait(trait_bound) => trait_bound
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|segment| segment.ident == *contract_name),
+                _ => false,
+            });
+            if !listed {
+                bounds.insert(0, syn::parse_quote!(#contract));
+            }
+        }
+        bounds
+    }
+
+    /// Whether `name` is a method implemented by the role, as opposed to a method of the player
+    pub fn has_method(&self, name: &syn::Ident) -> bool {
+        self.methods.iter().any(|method| {
+            matches!(method, FunctionDescription::Implementation { .. }) && method.get_name() == name
+        })
+    }
+
+    /// The bound of the players declaring `method`, as listed in their contracts: the
+    /// contract of the module declaring it, else the first bound defined elsewhere, e.g.
+    /// `Display` for `to_string`. The role's own name for roles without any bound.
+    pub fn contract_of(&self, method: &syn::Ident) -> String {
+        let known: Vec<&TraitInfo> = self.contract.iter().chain(&self.supertrait_contracts).collect();
+        let bounds = self.trait_bounds();
+        let declared_by = |bound: &syn::TraitBound| {
+            let name = &bound.path.segments.last()?.ident;
+            known.iter().find(|contract| contract.name == *name)
+        };
+        bounds
+            .iter()
+            .find(|bound| {
+                declared_by(bound).is_some_and(|contract| contract.functions.iter().any(|f| f.get_name() == method))
+            })
+            .or_else(|| bounds.iter().find(|bound| declared_by(bound).is_none()))
+            .or(bounds.first())
+            .map(compact)
+            .unwrap_or_else(|| self.name.to_string())
+    }
+
+    /// Whether the players are bound by a trait named `name`, e.g. `AccountContract`
+    pub fn has_bound(&self, name: &syn::Ident) -> bool {
+        self.trait_bounds()
+            .iter()
+            .any(|bound| bound.path.segments.last().is_some_and(|segment| segment.ident == *name))
+    }
+
+    fn trait_bounds(&self) -> Vec<syn::TraitBound> {
+        self.player_bounds()
+            .into_iter()
+            .filter_map(|bound| match bound {
+                syn::TypeParamBound::Trait(trait_bound) => Some(trait_bound),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// What the role method `name` borrows from the context
+    pub fn borrows(&self, name: &syn::Ident) -> &RoleMethodBorrows {
+        self.borrows.get(&name.to_string()).unwrap_or(&RoleMethodBorrows::Context)
+    }
+
+    /// How the role method `name` takes `self`, `None` for associated functions
+    pub fn receiver(&self, name: &syn::Ident) -> Option<SelfType> {
+        self.methods
+            .iter()
+            .find(|method| method.get_name() == name)
+            .and_then(|method| method.get_params().first().filter(|p| p.is_self()).map(|p| p.get_self_type()))
+    }
+}
+
+/// Reads the contract given explicitly with `#[role(contract = path::to::Contract)]`
+pub fn analyze_role_attribute(item_trait: &syn::ItemTrait) -> Option<syn::Path> {
+    let attr = item_trait.attrs.iter().find(|attr| attr.path().is_ident("role"))?;
+    let mut contract = None;
+    attr.parse_nested_meta(|meta| {
+        if meta.path.is_ident("contract") {
+            contract = Some(meta.value()?.parse::<syn::Path>()?);
+            Ok(())
+        } else {
+            Err(meta.error("expected `contract = path::to::Contract`"))
+        }
+    })
+    .map(|()| contract)
+    .unwrap_or_else(|err| {
+        emit_error(MacroError::from(err));
+        None
+    })
+}
+
+/// Collects the names of contracts brought into the module by `use` items
+pub fn analyze_imported_contracts(item_use: &syn::ItemUse, imported: &mut Vec<syn::Ident>) {
+    fn walk(tree: &syn::UseTree, imported: &mut Vec<syn::Ident>) {
+        match tree {
+            syn::UseTree::Path(path) => walk(&path.tree, imported),
+            syn::UseTree::Name(name) if name.ident.to_string().ends_with("Contract") => {
+                imported.push(name.ident.clone())
+            }
+            syn::UseTree::Rename(rename) if rename.rename.to_string().ends_with("Contract") => {
+                imported.push(rename.rename.clone())
+            }
+            syn::UseTree::Group(group) => group.items.iter().for_each(|tree| walk(tree, imported)),
+            _ => (),
+        }
+    }
+    walk(&item_use.tree, imported)
+}
+
+pub fn to_snake_case(pascal_case: &str) -> String {
+    let mut result = String::new();
+    let mut chars = pascal_case.chars().peekable();
+
+    while let Some(current) = chars.next() {
+        if current.is_uppercase() {
+            if !result.is_empty() &&
+               // Check if previous char wasn't an underscore
+               !result.ends_with('_') &&
+               // Check if next char isn't uppercase (handles acronyms like "HTTP")
+               !(chars.peek().is_some_and(|next| next.is_uppercase()))
+            {
+                result.push('_');
+            }
+            result.push(current.to_lowercase().next().unwrap());
+        } else {
+            result.push(current);
+        }
+    }
+
+    result
+}
+
+pub(crate) fn to_role_name(trait_name: &str) -> String {
+    let s = to_snake_case(trait_name);
+    if s.ends_with("_role") {
+        s[..s.len() - 5].to_owned()
+    } else {
+        s
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::{BTreeMap, HashMap};
+
+use proc_macro2::{TokenStream, TokenTree};
+use syn::visit::Visit;
+use syn::{Expr, Member};
+
+use super::*;
+
+/// What a role method borrows from the context when it is called
+#[derive(Clone)]
+pub enum RoleMethodBorrows {
+    /// The method uses interactions, other roles or the context as a whole,
+    /// so it borrows the entire context
+    Context,
+    /// The method only uses its player and the listed data fields of the context
+    Fields {
+        player: SelfType,
+        fields: Vec<BorrowedField>,
+    },
+}
+
+/// A data field of the context used by a role method through `self.context.<field>`
+#[derive(Clone)]
+pub struct BorrowedField {
+    pub name: syn::Ident,
+    pub ty: syn::Type,
+    pub mutable: bool,
+}
+
+/// Works out what each method of `role` borrows from the context.
+///
+/// Methods that only touch the player and data fields borrow just those, so an interaction
+/// can use other roles while the method runs. Anything else, including calls to a role
+/// method that needs the whole context, falls back to borrowing the entire context.
+pub fn analyze_role_borrows(
+    role: &Role,
+    properties: &[PropertyInfo],
+) -> HashMap<String, RoleMethodBorrows> {
+    let role_field = to_role_name(&role.name.to_string());
+    let plays_role = properties
+        .iter()
+        .any(|prop| prop.is_role() && prop.get_name() == role_field);
+    let data_fields: HashMap<String, (syn::Ident, syn::Type)> = properties
+        .iter()
+        .filter(|prop| !prop.is_role())
+        .map(|prop| (prop.get_name().to_string(), (prop.get_name(), prop.get_ty())))
+        .collect();
+
+    // The direct uses of each method, before following calls to other role methods
+    let mut uses: HashMap<String, Option<DirectUses>> = HashMap::new();
+    for method in &role.methods {
+        let FunctionDescription::Implementation { name, params, output, body, .. } = method else {
+            continue;
+        };
+        let player = params
+            .first()
+            .filter(|param| param.is_self())
+            .map(|param| param.get_self_type())
+            .filter(|player| *player != SelfType::Value);
+        let direct = match player {
+            // The player is passed next to the borrowed fields, so an elided lifetime in
+            // the result could no longer be tied to `self`
+            Some(player) if plays_role && !has_lifetimes(output) => {
+                let mut visitor = UsesVisitor {
+                    role,
+                    data_fields: &data_fields,
+                    mutable: player == SelfType::MutableReference,
+                    uses: DirectUses {
+                        player,
+                        fields: BTreeMap::new(),
+                        calls: vec![],
+                        context: false,
+                    },
+                };
+                visitor.visit_block(body);
+                Some(visitor.uses).filter(|uses| !uses.context)
+            }
+            _ => None,
+        };
+        uses.insert(name.to_string(), direct);
+    }
+
+    // Add the fields of called role methods until nothing changes
+    loop {
+        let mut changed = false;
+        for name in uses.keys().cloned().collect::<Vec<_>>() {
+            let Some(method) = uses[&name].clone() else {
+                continue;
+            };
+            let mut merged = method.clone();
+            for callee in &method.calls {
+                match uses.get(callee) {
+                    Some(Some(callee)) => {
+                        for (field, mutable) in &callee.fields {
+                            *merged.fields.entry(field.clone()).or_default() |= *mutable;
+                        }
+                    }
+                    _ => {
+                        merged.context = true;
+                    }
+                }
+            }
+            if merged.context {
+                uses.insert(name, None);
+                changed = true;
+            } else if merged.fields != method.fields {
+                uses.insert(name, Some(merged));
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    uses.into_iter()
+        .map(|(name, uses)| {
+            let borrows = match uses {
+                Some(uses) => RoleMethodBorrows::Fields {
+                    player: uses.player,
+                    fields: uses
+                        .fields
+                        .into_iter()
+                        .map(|(field, mutable)| BorrowedField {
+                            name: data_fields[&field].0.clone(),
+                            ty: data_fields[&field].1.clone(),
+                            mutable,
+                        })
+                        .collect(),
+                },
+                None => RoleMethodBorrows::Context,
+            };
+            (name, borrows)
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+struct DirectUses {
+    player: SelfType,
+    // Data fields by name, and whether they are used mutably
+    fields: BTreeMap<String, bool>,
+    // Methods of the same role called on `self`
+    calls: Vec<String>,
+    // Whether the context is used for anything but its data fields
+    context: bool,
+}
+
+struct UsesVisitor<'a> {
+    role: &'a Role,
+    data_fields: &'a HashMap<String, (syn::Ident, syn::Type)>,
+    // Whether the method may mutate the fields it uses
+    mutable: bool,
+    uses: DirectUses,
+}
+
+impl UsesVisitor<'_> {
+    /// The data field `expr` is a place in, if it is `self.context.<field>` or a field,
+    /// index or parenthesised expression based on it
+    fn place_field(expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Field(field_expr) => {
+                if let (Expr::Field(context), Member::Named(field)) =
+                    (&*field_expr.base, &field_expr.member)
+                {
+                    if is_self_context(context) {
+                        return Some(field.to_string());
+                    }
+                }
+                Self::place_field(&field_expr.base)
+            }
+            Expr::Index(index) => Self::place_field(&index.expr),
+            Expr::Paren(paren) => Self::place_field(&paren.expr),
+            _ => None,
+        }
+    }
+
+    fn use_field(&mut self, field: String, mutable: bool) {
+        if self.data_fields.contains_key(&field) {
+            *self.uses.fields.entry(field).or_default() |= mutable && self.mutable;
+        } else {
+            // Another role of the context
+            self.uses.context = true;
+        }
+    }
+
+    fn mutated(&mut self, place: &Expr) {
+        if let Some(field) = Self::place_field(place) {
+            self.use_field(field, true);
+        }
+    }
+
+    /// Macro arguments aren't parsed, so look for `self.context.<field>` and
+    /// `self.<method>(` in their tokens
+    fn visit_tokens(&mut self, tokens: TokenStream) {
+        let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+        let ident_at = |i: usize| match tokens.get(i) {
+            Some(TokenTree::Ident(ident)) => Some(ident.to_string()),
+            _ => None,
+        };
+        let dot_at = |i: usize| matches!(tokens.get(i), Some(TokenTree::Punct(p)) if p.as_char() == '.');
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                TokenTree::Group(group) => self.visit_tokens(group.stream()),
+                TokenTree::Ident(ident) if ident == "self" && dot_at(i + 1) => {
+                    match ident_at(i + 2).as_deref() {
+                        Some("context") => match (dot_at(i + 3), ident_at(i + 4)) {
+                            // Fields used inside macros such as `write!` may be mutated
+                            (true, Some(field)) if !matches!(tokens.get(i + 5), Some(TokenTree::Group(_))) => {
+                                self.use_field(field, true)
+                            }
+                            _ => self.uses.context = true,
+                        },
+                        Some(method) => {
+                            let method = syn::Ident::new(method, proc_macro2::Span::call_site());
+                            if self.role.has_method(&method) {
+                                self.uses.calls.push(method.to_string());
+                            }
+                        }
+                        None => (),
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for UsesVisitor<'_> {
+    fn visit_expr(&mut self, expr: &'ast Expr) {
+        match expr {
+            Expr::Field(field_expr) if is_self_context(field_expr) => {
+                // `self.context` on its own
+                self.uses.context = true;
+                return;
+            }
+            Expr::Field(field_expr) => {
+                if let (Expr::Field(context), Member::Named(field)) =
+                    (&*field_expr.base, &field_expr.member)
+                {
+                    if is_self_context(context) {
+                        self.use_field(field.to_string(), false);
+                        return;
+                    }
+                }
+            }
+            Expr::Assign(assign) => self.mutated(&assign.left),
+            Expr::Binary(binary) if is_compound_assignment(&binary.op) => self.mutated(&binary.left),
+            Expr::Reference(reference) if reference.mutability.is_some() => self.mutated(&reference.expr),
+            // The method may take `&mut self`
+            Expr::MethodCall(method_call) => {
+                self.mutated(&method_call.receiver);
+                if let Expr::Path(receiver) = &*method_call.receiver {
+                    if receiver.path.is_ident("self") && self.role.has_method(&method_call.method) {
+                        self.uses.calls.push(method_call.method.to_string());
+                    }
+                }
+            }
+            _ => (),
+        }
+        syn::visit::visit_expr(self, expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        self.visit_tokens(mac.tokens.clone());
+    }
+}
+
+/// Whether `expr` is `self.context`
+pub(crate) fn is_self_context(expr: &syn::ExprField) -> bool {
+    matches!((&*expr.base, &expr.member), (Expr::Path(base), Member::Named(member))
+        if base.path.is_ident("self") && member == "context")
+}
+
+fn is_compound_assignment(op: &syn::BinOp) -> bool {
+    use syn::BinOp::*;
+    matches!(
+        op,
+        AddAssign(_) | SubAssign(_) | MulAssign(_) | DivAssign(_) | RemAssign(_) | BitXorAssign(_)
+            | BitAndAssign(_) | BitOrAssign(_) | ShlAssign(_) | ShrAssign(_)
+    )
+}
+
+fn has_lifetimes(output: &syn::ReturnType) -> bool {
+    fn walk(tokens: TokenStream) -> bool {
+        tokens.into_iter().any(|token| match token {
+            TokenTree::Punct(punct) => punct.as_char() == '&' || punct.as_char() == '\'',
+            TokenTree::Group(group) => walk(group.stream()),
+            _ => false,
+        })
+    }
+    walk(quote::ToTokens::to_token_stream(output))
+}
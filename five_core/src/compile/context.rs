@@ -0,0 +1,741 @@
+use std::collections::HashMap;
+use quote::ToTokens;
+use function_descriptor::trait_method;
+use impl_block::CompiledImplBlock;
+use intercept::intercept_interaction;
+use proc_macro2::TokenStream;
+use macro_args::rewrite_macro;
+use role::{role_method_call, Caller, CompiledRole};
+use syn::{visit_mut::VisitMut, Block, Expr, ItemStruct};
+
+use crate::analysis::errors::{emit_error, MacroError};
+use crate::analysis::players::{self, Player, Players, VisitPlayers};
+use crate::analysis::{
+    is_phantom_field, new_role_generic_name, phantom_field_name, replace_role_name, role_generic_name,
+    to_role_name, with_role_name, AsyncStrategy, ContextInfo, FunctionDescription, GenericsInfo, ImplBlockInfo,
+    ParameterInfo, Role, SelfType,
+};
+
+use super::*;
+
+impl Compiler<ContextInfo> for ContextInfo {
+    fn compile(&self) -> CompiledContext {
+
+        let base = self.compile_struct();
+
+        // Create the context trait from impl block methods. The interactions of all impl
+        // blocks implement the one context trait, so they are merged into a single block
+        let mut blocks = self.impl_blocks
+            .iter()
+            .cloned()
+            .reduce(|mut merged: ImplBlockInfo, block| {
+                merged.functions.extend(block.functions);
+                merged
+            })
+            .map(|mut block| {
+                block.attrs = self.attrs.clone();
+                block
+            })
+            .into_iter()
+            .collect::<Vec<ImplBlockInfo>>();
+
+        // Add the with_<role>/replace_<role> methods for recasting single roles. They are
+        // part of the context trait, so they go into the same impl as the interactions
+        if let Some(block) = blocks.first_mut() {
+            block.functions.extend(self.compile_role_binders(&base));
+        }
+        
+        // Async interactions either return `Send` futures or follow the `#[async_trait]`
+        // contracts, which box them
+        let send = self.is_async() && matches!(self.async_strategy, AsyncStrategy::Send);
+        let async_trait = match &self.async_strategy {
+            AsyncStrategy::AsyncTrait(attr) if self.is_async() => Some((**attr).clone()),
+            _ => None,
+        };
+        blocks.iter_mut().for_each(|block| block.attrs.extend(async_trait.clone()));
+
+        // Get all method signatures for the trait, preserving generics
+        let trait_methods = blocks
+            .iter()
+            .flat_map(|block| block.functions.iter().map(|f| {
+                trait_method(f, send).unwrap_or_else(|| panic!("Expected implementation"))
+            }))
+            .collect::<Vec<syn::TraitItem>>();
+
+        // Get the generics from the base struct
+        let generics = GenericsInfo::from_syn_generics(&base.generics);
+
+        // Create roles_map for method compilation
+        let roles_map: HashMap<String, Role> = self
+            .roles
+            .iter()
+            .map(|r| (to_role_name(&r.name.to_string()), r.clone()))
+            .collect();
+
+        // No special handling for specific types/methods - use trait methods as-is
+        let trait_name = syn::Ident::new("Context", self.name.span());
+
+        // Create the trait definition with the same generic parameters as the Context struct
+        let struct_generics_params = generics.get_params();
+        let struct_where_clause = generics.get_where_clause();
+        
+        // Without `send` or `#[async_trait]` the futures are deliberately left without
+        // auto trait bounds, which the lint for public traits would otherwise flag
+        let native_async = (self.is_async() && matches!(self.async_strategy, AsyncStrategy::Native))
+            .then(|| quote::quote!(#[allow(async_fn_in_trait)]));
+
+        let five = &self.crate_path;
+        // Use the same generic parameters for both the trait and struct
+        let trait_def = quote::quote! {
+            #async_trait
+            #native_async
+            pub trait #trait_name<#(#struct_generics_params),*>: #five::Describe #struct_where_clause {
+                #(#trait_methods)*
+            }
+        };
+            
+        let context_trait = syn::parse2(trait_def).unwrap();
+
+        // Compile roles
+        let roles = self
+            .roles
+            .iter()
+            .map(|r| r.compile(&roles_map, &base, &self.async_strategy))
+            .collect();
+
+        // Create impl blocks that implement Context<T, ...>
+        let context_methods = 
+            blocks
+                .iter()
+                .map(|b| {
+                    let mut impl_block = self.compile_context_methods(&roles_map, &base.generics, b.clone());
+                    impl_block.generics = generics.clone();
+                    
+                    // Implement Context<T, ...> with the same generic parameters
+                    let generic_args: Vec<syn::GenericArgument> = generics.get_params().iter().map(|param| {
+                        match param {
+                            syn::GenericParam::Type(tp) => syn::GenericArgument::Type(
+                                syn::Type::Path(syn::TypePath {
+                                    qself: None,
+                                    path: syn::Path::from(tp.ident.clone()),
+                                }),
+                            ),
+                            syn::GenericParam::Lifetime(l) => syn::GenericArgument::Lifetime(l.lifetime.clone()),
+                            syn::GenericParam::Const(c) => syn::GenericArgument::Const(
+                                syn::Expr::Path(syn::ExprPath {
+                                    attrs: vec![],
+                                    qself: None,
+                                    path: syn::Path::from(c.ident.clone()),
+                                }),
+                            ),
+                        }
+                    }).collect();
+                    
+                    let trait_with_generics = {
+                        let mut segments = syn::punctuated::Punctuated::new();
+                        let mut args = syn::punctuated::Punctuated::new();
+                        
+                        for arg in &generic_args {
+                            args.push(arg.clone());
+                        }
+                        
+                        let trait_segment = syn::PathSegment {
+                            ident: trait_name.clone(),
+                            arguments: if args.is_empty() {
+                                syn::PathArguments::None
+                            } else {
+                                syn::PathArguments::AngleBracketed(
+                                    syn::AngleBracketedGenericArguments {
+                                        colon2_token: None,
+                                        lt_token: syn::Token![<](trait_name.span()),
+                                        args,
+                                        gt_token: syn::Token![>](trait_name.span()),
+                                    }
+                                )
+                            }
+                        };
+                        
+                        segments.push(trait_segment);
+                        
+                        syn::Path {
+                            leading_colon: None,
+                            segments,
+                        }
+                    };
+                    
+                    impl_block.implemented_traits = vec![trait_with_generics];
+                    
+                    impl_block
+                })
+                .collect();
+
+        CompiledContext {
+            roles,
+            context_methods,
+            base,
+            context_trait,
+        }
+    }
+
+    type Output = CompiledContext;
+}
+
+#[derive(Clone)]
+pub struct CompiledContext {
+    pub roles: Vec<CompiledRole>,
+    pub context_methods: Vec<CompiledImplBlock>,
+    pub base: ItemStruct,
+    pub context_trait: syn::ItemTrait,
+}
+
+impl Compiled<ContextInfo> for CompiledContext {
+    fn emit(&self) -> TokenStream {
+        let mut ts = TokenStream::new();
+        
+        // First emit the struct definition
+        ts.extend(self.base.to_token_stream());
+
+        // Then emit the trait definition
+        ts.extend(self.context_trait.to_token_stream());
+
+        // Then emit the role implementations
+        ts.extend(TokenStream::from_iter(self.roles.iter().map(|r| r.emit())));
+
+        // Finally emit the context method implementations
+        ts.extend(TokenStream::from_iter(
+            self.context_methods.iter().flat_map(|r| r.emit()),
+        ));
+        
+        ts
+    }
+}
+
+impl ContextInfo {
+    fn is_primitive_type(ty: &syn::Type) -> bool {
+        match ty {
+            syn::Type::Path(type_path) => {
+                if let Some(segment) = type_path.path.segments.last() {
+                    matches!(
+                        segment.ident.to_string().as_str(),
+                        "i32" | "i64" | "f32" | "f64" | "bool" | "char" | "str"
+                    )
+                } else {
+                    false
+                }
+            }
+            _ => false, // Treat all other types as non-primitive
+        }
+    }
+
+
+    /// Creates `with_<role>` and `replace_<role>` for every role field, so a single role
+    /// can be recast while the remaining players stay bound.
+    ///
+    /// `with_<role>` consumes the context and returns a new context where the role is
+    /// played by an object of a (possibly) different type, while `replace_<role>` swaps
+    /// in a player of the same type and hands back the previous one.
+    fn compile_role_binders(&self, base: &ItemStruct) -> Vec<FunctionDescription> {
+        let fields: Vec<&syn::Field> = base.fields.iter().collect();
+        let mut functions = Vec::new();
+
+        for prop in &self.properties {
+            if Self::is_primitive_type(&prop.get_ty()) || !prop.is_role() {
+                continue;
+            }
+            let field_name = prop.get_name();
+            let generic_name = role_generic_name(&field_name);
+            let bounds = base.generics.type_params()
+                .find(|tp| tp.ident == generic_name)
+                .map(|tp| tp.bounds.clone())
+                .unwrap_or_default();
+            let new_generic_name = new_role_generic_name(&field_name);
+
+            // The type of the rebound context, where only the recast role changes
+            let new_args = base.generics.params.iter().map(|param| match param {
+                syn::GenericParam::Type(tp) if tp.ident == generic_name => quote::quote!(#new_generic_name),
+                syn::GenericParam::Type(tp) => tp.ident.to_token_stream(),
+                syn::GenericParam::Lifetime(l) => l.lifetime.to_token_stream(),
+                syn::GenericParam::Const(c) => c.ident.to_token_stream(),
+            });
+            let new_context_type: syn::Type = syn::parse_quote!(Context<#(#new_args),*>);
+
+            let field_values = fields.iter().map(|field| {
+                let name = field.ident.as_ref().unwrap();
+                if *name == field_name {
+                    quote::quote!(#name: #name)
+                } else if is_phantom_field(name) {
+                    quote::quote!(#name: ::core::marker::PhantomData)
+                } else {
+                    quote::quote!(#name: self.#name)
+                }
+            });
+
+            functions.push(FunctionDescription::new_implementation(
+                with_role_name(&field_name),
+                vec![
+                    ParameterInfo::SelfRef,
+                    ParameterInfo::Typed {
+                        name: field_name.clone(),
+                        ty: syn::parse_quote!(#new_generic_name),
+                    },
+                ],
+                GenericsInfo::new(vec![syn::parse_quote!(#new_generic_name: #bounds)], None),
+                syn::parse_quote!(-> #new_context_type),
+                syn::parse_quote!({
+                    Context {
+                        #(#field_values),*
+                    }
+                }),
+                None,
+                vec![],
+            ));
+
+            functions.push(FunctionDescription::new_implementation(
+                replace_role_name(&field_name),
+                vec![
+                    ParameterInfo::MutableReference(Box::new(ParameterInfo::SelfRef)),
+                    ParameterInfo::Typed {
+                        name: field_name.clone(),
+                        ty: syn::parse_quote!(#generic_name),
+                    },
+                ],
+                GenericsInfo::new(vec![], None),
+                syn::parse_quote!(-> #generic_name),
+                syn::parse_quote!({
+                    ::core::mem::replace(&mut self.#field_name, #field_name)
+                }),
+                None,
+                vec![],
+            ));
+        }
+
+        functions
+    }
+
+    fn compile_context_methods(
+        &self,
+        roles_map: &HashMap<String, Role>,
+        context_generics: &syn::Generics,
+        impl_block: ImplBlockInfo,
+    ) -> CompiledImplBlock {
+        let functions = impl_block
+            .functions
+            .iter()
+            .map(|func| {
+                match func {
+                    FunctionDescription::Implementation {
+                        name,
+                        params,
+                        generics,
+                        output,
+                        body,
+                        asyncness,
+                        attrs,
+                    } => {
+                        let receiver = params.first().filter(|p| p.is_self()).map(|p| p.get_self_type());
+                        let mut body = body.clone();
+                        rewrite_role_access(roles_map, context_generics, receiver, &mut body);
+                        // The generated binders only recast roles, they aren't intercepted
+                        if let Some(interceptor) = self.interceptor.as_ref().filter(|_| self.is_interaction(name)) {
+                            body = intercept_interaction(
+                                &self.crate_path,
+                                interceptor,
+                                &self.name,
+                                name,
+                                output,
+                                asyncness.is_some(),
+                                &body,
+                            );
+                        }
+                        
+                        // Just preserve all existing generics as is - we'll handle 
+                        // the correct generics at the impl block level
+                        FunctionDescription::new_implementation(
+                            name.clone(),
+                            params.clone(),
+                            generics.clone(),
+                            output.clone(),
+                            body,
+                            *asyncness,
+                            attrs.clone()
+                        )
+                    }
+                    f => f.clone(),
+                }
+            })
+            .collect();
+
+        // Compile the impl block
+        let mut compiled = ImplBlockInfo {
+            functions,
+            generics: self.generics.clone(), // Use the struct generics directly
+            ..impl_block
+        }
+        .compile();
+        
+        // Implement for the context struct with all of its generics
+        let context_name = &self.name;
+        let (_, type_generics, _) = context_generics.split_for_impl();
+        compiled.self_ty = syn::parse_quote!(#context_name #type_generics);
+
+        compiled
+    }
+
+    fn compile_struct(&self) -> ItemStruct {
+        // Start with the user-defined generics from the original Context struct
+        let mut generics_params = Vec::new();
+        
+        // First add all of the user's original generic params - preserving their original bounds
+        for param in self.generics.get_params() {
+            generics_params.push(param.clone());
+        }
+
+        // Map properties to their corresponding generic parameters or original types
+        let property_generics: Vec<_> = self
+            .properties
+            .iter()
+            .map(|prop| {
+                if Self::is_primitive_type(&prop.get_ty()) {
+                    // Primitive type: use original type
+                    (prop.get_name().clone(), prop.get_ty().clone(), None)
+                } else {
+                    // Check if this type matches a trait role with a suffix like "Role"
+                    if prop.is_role() {
+                        // Get the role type path
+                        let role_path = match &prop.get_ty() {
+                            syn::Type::Path(type_path) => type_path.path.clone(),
+                            _ => panic!("Expected a Path type"),
+                        };
+                        
+                        // Try to find the role in our roles list. A role trait defined outside
+                        // the module is reused as is, so the player must implement it
+                        let mut bounds = self.roles.iter()
+                            .find(|r| role_path.is_ident(&r.name))
+                            .map(|role| role.player_bounds())
+                            .unwrap_or_else(|| syn::parse_quote!(#role_path));
+                        // Futures holding a reference to the context are `Send` only if the
+                        // players can be shared and sent between threads
+                        if self.needs_send() {
+                            bounds.push(syn::parse_quote!(::core::marker::Send));
+                            bounds.push(syn::parse_quote!(::core::marker::Sync));
+                        }
+                            
+                        let generic_name = role_generic_name(&prop.get_name());
+
+                        // Return the generic type instead of the original type
+                        (
+                            prop.get_name().clone(),
+                            syn::Type::Path(syn::TypePath {
+                                qself: None,
+                                path: syn::Path::from(generic_name.clone()),
+                            }),
+                            Some((generic_name, bounds)),
+                        )
+                    } else {
+                        // Non-role type: keep as is
+                        (prop.get_name().clone(), prop.get_ty().clone(), None)
+                    }
+                }
+            })
+            .collect();
+
+        // Now add the role trait generic params to our list,
+        // making sure we're not duplicating any that already exist
+        for (_, _, player) in property_generics.iter() {
+            if let Some((generic_name, bounds)) = player {
+                // Check if the parameter already exists
+                let param_exists = generics_params.iter().any(|param| {
+                    if let syn::GenericParam::Type(type_param) = param {
+                        type_param.ident == *generic_name
+                    } else {
+                        false
+                    }
+                });
+
+                if !param_exists {
+                    generics_params.push(syn::GenericParam::Type(syn::TypeParam {
+                        attrs: vec![],
+                        ident: generic_name.clone(),
+                        bounds: bounds.clone(),
+                        eq_token: None,
+                        default: None,
+                        colon_token: if bounds.is_empty() { None } else { Some(Default::default()) },
+                    }));
+                }
+            }
+        }
+
+        // Generate fields for the struct
+        let mut fields: Vec<syn::Field> = property_generics
+            .into_iter()
+            .zip(&self.properties)
+            .map(|((field_name, field_type, _), prop)| syn::Field {
+                mutability: syn::FieldMutability::None,
+                attrs: prop.get_attrs().to_vec(),
+                vis: syn::Visibility::Inherited,
+                ident: Some(field_name),
+                colon_token: Some(Default::default()),
+                ty: field_type,
+            })
+            .collect();
+            
+        // Add PhantomData for all generic type parameters that aren't used in fields
+        // This prevents "unused type parameter" warnings
+        for param in &generics_params {
+            if let syn::GenericParam::Type(tp) = param {
+                let param_name = &tp.ident;
+                
+                // Check if this parameter is used in any field
+                let used_in_field = fields.iter().any(|field| {
+                    if field.ident.is_some() {
+                        if let syn::Type::Path(type_path) = &field.ty {
+                            if let Some(segment) = type_path.path.segments.last() {
+                                return segment.ident == *param_name;
+                            }
+                        }
+                    }
+                    false
+                });
+                
+                if !used_in_field {
+                    // Add a PhantomData field for this type parameter
+                    let phantom_field = syn::Field {
+                        attrs: vec![],
+                        mutability: syn::FieldMutability::None, 
+                        vis: syn::Visibility::Inherited,
+                        ident: Some(phantom_field_name(param_name)),
+                        colon_token: Some(Default::default()),
+                        ty: syn::parse_quote!(::core::marker::PhantomData<#param_name>),
+                    };
+                    
+                    fields.push(phantom_field);
+                }
+            }
+        }
+
+        // Preserve the original where clause, which is important for bounds like for<'de>
+        let where_clause = self.generics.get_where_clause().clone();
+        
+        // Finalize generics with all parameters
+        let mut generics = syn::Generics {
+            lt_token: if generics_params.is_empty() { None } else { Some(Default::default()) },
+            params: syn::punctuated::Punctuated::new(),
+            gt_token: if generics_params.is_empty() { None } else { Some(Default::default()) },
+            where_clause,
+        };
+        
+        // Add all generics params
+        for param in generics_params {
+            generics.params.push(param);
+        }
+
+        // Construct the struct
+        syn::ItemStruct {
+            attrs: vec![],
+            vis: syn::parse_quote!(pub),
+            struct_token: syn::token::Struct {
+                span: self.name.span(),
+            },
+            ident: self.name.clone(),
+            generics,
+            fields: syn::Fields::Named(syn::FieldsNamed {
+                brace_token: Default::default(),
+                named: syn::punctuated::Punctuated::from_iter(fields),
+            }),
+            semi_token: None,
+        }
+    }
+}
+
+/// Rewrites calls to role methods into calls through the role trait implemented by the context.
+///
+/// Supported forms are
+/// - method calls on the role field, `self.ledger.add(entry)`
+/// - method calls on a local alias of the role field, `let l = &mut self.ledger; l.add(entry)`
+/// - UFCS calls naming the role trait, `LedgerRole::add(&mut self.ledger, entry)`
+///
+/// Role methods used as function values, e.g. `.for_each(LedgerRole::add)`, can't be
+/// rewritten and are reported as errors.
+pub(super) fn rewrite_role_access(
+    roles: &HashMap<String, Role>,
+    context_generics: &syn::Generics,
+    receiver: Option<SelfType>,
+    block: &mut Block,
+) {
+    struct RoleMethodRewriter<'a> {
+        roles: &'a HashMap<String, Role>,
+        context_generics: &'a syn::Generics,
+        // How the function being rewritten takes `self`
+        receiver: Option<SelfType>,
+        players: Players,
+    }
+
+    impl RoleMethodRewriter<'_> {
+        /// The name of the role played by `expr`, if it is `self.<role>`, a reference to
+        /// it, or a local alias of it
+        fn role_of(&self, expr: &Expr) -> Option<String> {
+            self.players.player(expr).map(|player| player.field().to_string())
+        }
+
+        /// How the function is called when `player` is a local alias of the role field
+        fn caller(&self, player: &Expr) -> Caller {
+            match self.players.player(player) {
+                Some(Player::Alias { name, by_ref: true, .. }) => Caller::Alias(Box::new(syn::parse_quote!(*#name)), self.receiver),
+                Some(Player::Alias { name, by_ref: false, .. }) => Caller::Alias(Box::new(syn::parse_quote!(#name)), self.receiver),
+                _ => Caller::Context(self.receiver),
+            }
+        }
+
+        /// The role and method named by a path such as `LedgerRole::add`. Qualified paths,
+        /// `<Self as LedgerRole>::add`, already dispatch through the role trait
+        fn role_method_path(&self, path: &syn::ExprPath) -> Option<(String, syn::Ident)> {
+            let len = path.path.segments.len();
+            if path.qself.is_some() || len < 2 {
+                return None;
+            }
+            let trait_name = &path.path.segments[len - 2].ident;
+            let method = &path.path.segments[len - 1].ident;
+            self.roles
+                .iter()
+                .find(|(_, role)| role.name == *trait_name && role.has_method(method))
+                .map(|(role_name, _)| (role_name.clone(), method.clone()))
+        }
+
+        fn role_method_call(
+            &self,
+            role_name: &str,
+            player: &Expr,
+            method: &syn::Ident,
+            turbofish: Option<&syn::AngleBracketedGenericArguments>,
+            args: syn::punctuated::Punctuated<Expr, syn::Token![,]>,
+        ) -> Expr {
+            role_method_call(
+                &self.roles[role_name],
+                method,
+                turbofish,
+                args,
+                self.context_generics,
+                self.caller(player),
+            )
+        }
+    }
+
+    impl VisitMut for RoleMethodRewriter<'_> {
+        fn visit_expr_mut(&mut self, expr: &mut Expr) {
+            match expr {
+                Expr::MethodCall(method_call) => {
+                    if let Some(role_name) = self.role_of(&method_call.receiver) {
+                        // Only rewrite methods of the role, the player's own
+                        // methods are called on the field as written
+                        if self.roles[&role_name].has_method(&method_call.method) {
+                            let mut args = method_call.args.clone();
+                            for arg in args.iter_mut() {
+                                self.visit_expr_mut(arg);
+                            }
+                            *expr = self.role_method_call(
+                                &role_name,
+                                &method_call.receiver,
+                                &method_call.method,
+                                method_call.turbofish.as_ref(),
+                                args,
+                            );
+                            return;
+                        }
+                    }
+                }
+                Expr::Call(call) => {
+                    if let Expr::Path(func) = &*call.func {
+                        if let Some((role_name, method)) = self.role_method_path(func) {
+                            let mut args = call.args.clone().into_iter();
+                            let player = match args.next() {
+                                Some(player) if self.role_of(&player) == Some(role_name.clone()) => player,
+                                _ => {
+                                emit_error(MacroError::new(
+                                    format!(
+                                        "the first argument of `{}` must be the role player `self.{}`",
+                                        quote::quote!(#func).to_string().replace(' ', ""),
+                                        role_name
+                                    ),
+                                    method.span(),
+                                ));
+                                return;
+                                }
+                            };
+                            let mut args: syn::punctuated::Punctuated<Expr, syn::Token![,]> =
+                                args.collect();
+                            for arg in args.iter_mut() {
+                                self.visit_expr_mut(arg);
+                            }
+                            let turbofish = match &func.path.segments.last().unwrap().arguments {
+                                syn::PathArguments::AngleBracketed(args) => Some(args.clone()),
+                                _ => None,
+                            };
+                            *expr = self.role_method_call(&role_name, &player, &method, turbofish.as_ref(), args);
+                            return;
+                        }
+                    }
+                }
+                Expr::Path(expr_path) => {
+                    if let Some((role_name, method)) = self.role_method_path(expr_path) {
+                        emit_error(MacroError::new(
+                            format!(
+                                "role methods can't be used as function values; \
+                                 use a closure instead, e.g. `|x| self.{}.{}(x)`",
+                                role_name, method
+                            ),
+                            method.span(),
+                        ));
+                        return;
+                    }
+                }
+                _ => (),
+            }
+            players::visit_expr_mut(self, expr);
+        }
+
+        fn visit_block_mut(&mut self, block: &mut Block) {
+            players::visit_block_mut(self, block);
+        }
+
+        fn visit_local_mut(&mut self, local: &mut syn::Local) {
+            players::visit_local_mut(self, local);
+        }
+
+        fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
+            rewrite_macro(self, mac);
+        }
+    }
+
+    impl VisitPlayers for RoleMethodRewriter<'_> {
+        fn players(&mut self) -> &mut Players {
+            &mut self.players
+        }
+    }
+
+    let mut rewriter = RoleMethodRewriter {
+        roles,
+        context_generics,
+        receiver,
+        players: Players::new(roles.keys().cloned(), None),
+    };
+    rewriter.visit_block_mut(block);
+}
+
+impl GenericsInfo {
+    pub fn to_syn_generics(&self) -> syn::Generics {
+        syn::Generics {
+            lt_token: if self.get_params().is_empty() {
+                None
+            } else {
+                Some(Default::default())
+            },
+            params: self.get_params().clone().into_iter().collect(),
+            gt_token: if self.get_params().is_empty() {
+                None
+            } else {
+                Some(Default::default())
+            },
+            where_clause: self.get_where_clause().clone(),
+        }
+    }
+}
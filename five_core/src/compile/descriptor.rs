@@ -0,0 +1,65 @@
+use quote::quote;
+
+use crate::analysis::{compact, context_trait_name, descriptor_name, FunctionDescription, ModuleInfo, RoleField};
+
+/// `DESCRIPTOR`, a `five::ContextDescriptor` listing the roles, data and interactions of
+/// the context as written in the module
+pub fn compile_descriptor(module: &ModuleInfo) -> syn::ItemConst {
+    let context = &module.context;
+    let five = &context.crate_path;
+    let name = module.module_name.to_string();
+    let context_trait = context_trait_name(&module.module_name).to_string();
+
+    let roles = context.role_fields().into_iter().map(|RoleField { field, role_name, role, contracts }| {
+        let field = field.to_string();
+        let contract_methods = role
+            .filter(|role| role.external_contract.is_none())
+            .and_then(|role| role.contract.as_ref())
+            .map(|contract| methods(five, &contract.functions))
+            .unwrap_or_default();
+        let role_methods = role.map(|role| methods(five, &role.methods)).unwrap_or_default();
+        let role_name = role_name.to_string();
+        quote! {
+            #five::RoleDescriptor {
+                name: #role_name,
+                field: #field,
+                contracts: &[#(#contracts),*],
+                contract_methods: &[#(#contract_methods),*],
+                methods: &[#(#role_methods),*],
+            }
+        }
+    });
+
+    let data = context.properties.iter().filter(|prop| !prop.is_role()).map(|prop| {
+        let name = prop.get_name().to_string();
+        let ty = compact(&prop.get_ty());
+        quote!(#five::DataDescriptor { name: #name, ty: #ty })
+    });
+
+    let interactions: Vec<FunctionDescription> =
+        context.impl_blocks.iter().flat_map(|block| block.functions.clone()).collect();
+    let interactions = methods(five, &interactions);
+
+    let const_name = descriptor_name(&module.module_name);
+    syn::parse_quote! {
+        /// The roles, data and interactions of the context
+        pub const #const_name: #five::ContextDescriptor = #five::ContextDescriptor {
+            name: #name,
+            context_trait: #context_trait,
+            roles: &[#(#roles),*],
+            data: &[#(#data),*],
+            interactions: &[#(#interactions),*],
+        };
+    }
+}
+
+fn methods(five: &syn::Path, functions: &[FunctionDescription]) -> Vec<proc_macro2::TokenStream> {
+    functions
+        .iter()
+        .map(|function| {
+            let name = function.get_name().to_string();
+            let signature = function.signature();
+            quote!(#five::MethodDescriptor { name: #name, signature: #signature })
+        })
+        .collect()
+}
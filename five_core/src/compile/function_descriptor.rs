@@ -53,7 +53,7 @@ impl Compiled<CompiledFunctionDescription> for CompiledFunctionDescription {
                     let tokens = self_param.to_token_stream();
                     let arg: syn::FnArg = syn::parse2(tokens).unwrap();
                     inputs.push_value(arg);
-                    inputs.push_punct(syn::Token![,](proc_macro2::Span::call_site()));
+                    inputs.push_punct(syn::Token![,](name.span()));
                 }
 
                 // Add remaining parameters
@@ -61,13 +61,13 @@ impl Compiled<CompiledFunctionDescription> for CompiledFunctionDescription {
                     let tokens = param.to_token_stream();
                     let arg: syn::FnArg = syn::parse2(tokens).unwrap();
                     inputs.push_value(arg);
-                    inputs.push_punct(syn::Token![,](proc_macro2::Span::call_site()));
+                    inputs.push_punct(syn::Token![,](name.span()));
                 }
 
                 // Create the function signature
                 let sig = syn::Signature {
                     constness: None,
-                    asyncness: asyncness.clone(),
+                    asyncness: *asyncness,
                     unsafety: None,
                     abi: None,
                     fn_token: syn::token::Fn::default(),
@@ -112,7 +112,7 @@ impl Compiler<CompiledFunctionDescription> for FunctionDescription {
                 params: params.clone(),
                 generics: generics.clone(),
                 output: output.clone(),
-                asyncness: asyncness.clone(),
+                asyncness: *asyncness,
                 attrs: attrs.clone(),
             },
             FunctionDescription::Implementation {
@@ -129,7 +129,7 @@ impl Compiler<CompiledFunctionDescription> for FunctionDescription {
                 generics: generics.clone(),
                 output: output.clone(),
                 body: Some(body.clone()),
-                asyncness: asyncness.clone(),
+                asyncness: *asyncness,
                 attrs: attrs.clone(),
             },
         }
@@ -137,3 +137,40 @@ impl Compiler<CompiledFunctionDescription> for FunctionDescription {
 
     type Output = CompiledFunctionDescription;
 }
+
+/// The declaration of an implemented function as a trait item, i.e. its signature without
+/// attributes or body. With `send`, an async function is declared as returning a `Send`
+/// future, which the implementation can still provide with `async fn`
+pub(super) fn trait_method(function: &FunctionDescription, send: bool) -> Option<syn::TraitItem> {
+    match function {
+        FunctionDescription::Implementation { name, params, generics, output, asyncness, .. } => {
+            let params = params.iter().map(|p| p.declared().to_token_stream());
+            let generic_params = generics.get_params();
+            let where_clause = generics.get_where_clause();
+
+            // Only add angle brackets if we have generic parameters
+            let generic_tokens = if !generic_params.is_empty() {
+                quote::quote!(<#(#generic_params),*>)
+            } else {
+                quote::quote!()
+            };
+
+            if send && asyncness.is_some() {
+                let output = match output {
+                    ReturnType::Default => quote::quote!(()),
+                    ReturnType::Type(_, ty) => ty.to_token_stream(),
+                };
+                Some(syn::parse_quote! {
+                    fn #name #generic_tokens (#(#params),*)
+                        -> impl ::core::future::Future<Output = #output> + ::core::marker::Send
+                        #where_clause;
+                })
+            } else {
+                Some(syn::parse_quote! {
+                    #asyncness fn #name #generic_tokens (#(#params),*) #output #where_clause;
+                })
+            }
+        }
+        FunctionDescription::Declaration { .. } => None,
+    }
+}
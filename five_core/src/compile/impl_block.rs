@@ -0,0 +1,57 @@
+use crate::analysis::{GenericsInfo, ImplBlockInfo};
+
+use super::{function_descriptor::CompiledFunctionDescription, Compiled, Compiler};
+
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct CompiledImplBlock {
+    pub generics: GenericsInfo,
+    pub for_lifetimes: Option<syn::Lifetime>,
+    pub implemented_traits: Vec<syn::Path>,
+    pub functions: Vec<CompiledFunctionDescription>,
+    pub self_ty: syn::Type,
+    pub attrs: Vec<syn::Attribute>,
+}
+
+impl Compiled<ImplBlockInfo> for CompiledImplBlock {
+    fn emit(&self) -> proc_macro2::TokenStream {
+        use quote::quote;
+
+        let items = self.functions.iter().map(|func| {
+            let mut item_fn: syn::ImplItemFn = syn::parse2(func.emit()).unwrap();
+            // Functions implementing a trait can't have a visibility
+            if !self.implemented_traits.is_empty() {
+                item_fn.vis = syn::Visibility::Inherited;
+            }
+            item_fn
+        });
+
+        let generics = self.generics.to_syn_generics();
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let traits = &self.implemented_traits;
+        let impl_trait = (!traits.is_empty()).then(|| quote!(#(#traits)+* for));
+        let self_ty = &self.self_ty;
+        let attrs = &self.attrs;
+        quote! {
+            #(#attrs)*
+            impl #impl_generics #impl_trait #self_ty #where_clause {
+                #(#items)*
+            }
+        }
+    }
+}
+
+impl Compiler<ImplBlockInfo> for ImplBlockInfo {
+    fn compile(&self) -> CompiledImplBlock {
+        CompiledImplBlock {
+            generics: self.generics.clone(),
+            for_lifetimes: self.for_lifetimes.clone(),
+            implemented_traits: self.implemented_traits.clone(),
+            functions: self.functions.iter().map(|f| f.compile()).collect(),
+            self_ty: self.self_ty.clone(),
+            attrs: self.attrs.clone(),
+        }
+    }
+
+    type Output = CompiledImplBlock;
+}
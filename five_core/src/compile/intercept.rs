@@ -0,0 +1,65 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+
+use crate::analysis::descriptor_name;
+
+/// The body of the interaction `name` run between the callbacks of the interceptor given
+/// with `#[five::context(intercept(Type))]`. The body becomes a closure, or a future for
+/// async interactions, so `return` and `?` leave the body rather than skipping the
+/// callbacks.
+pub(super) fn intercept_interaction(
+    five: &syn::Path,
+    interceptor: &syn::Type,
+    context_name: &syn::Ident,
+    name: &syn::Ident,
+    output: &syn::ReturnType,
+    is_async: bool,
+    body: &syn::Block,
+) -> syn::Block {
+    let descriptor = descriptor_name(context_name);
+    let interaction_name = name.to_string();
+    let interaction = quote!(#five::Interaction::new(&#descriptor, #interaction_name));
+
+    // `impl Trait` can't be named outside the signature, it's inferred from the body instead
+    let (result, annotation) = match output {
+        syn::ReturnType::Default => (quote!(()), quote!(-> ())),
+        syn::ReturnType::Type(_, ty) if mentions_impl(ty.to_token_stream()) => (quote!(_), quote!()),
+        syn::ReturnType::Type(_, ty) => (quote!(#ty), quote!(-> #ty)),
+    };
+    let helper = match (is_async, is_result(output)) {
+        (false, false) => quote!(intercept),
+        (false, true) => quote!(intercept_fallible),
+        (true, false) => quote!(intercept_async),
+        (true, true) => quote!(intercept_async_fallible),
+    };
+
+    let call: TokenStream = if is_async {
+        quote!(#five::intercept::#helper::<#interceptor, #result, _>(#interaction, async move #body).await)
+    } else {
+        quote!(#five::intercept::#helper::<#interceptor, #result, _>(#interaction, move || #annotation #body))
+    };
+    syn::parse_quote!({ #call })
+}
+
+/// Whether the interaction returns a `Result`, whose errors go to `on_error`
+fn is_result(output: &syn::ReturnType) -> bool {
+    match output {
+        syn::ReturnType::Type(_, ty) => match &**ty {
+            syn::Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Result"),
+            _ => false,
+        },
+        syn::ReturnType::Default => false,
+    }
+}
+
+fn mentions_impl(tokens: TokenStream) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(ident) => ident == "impl",
+        TokenTree::Group(group) => mentions_impl(group.stream()),
+        _ => false,
+    })
+}
@@ -0,0 +1,185 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::{visit_mut::VisitMut, Expr, Token};
+
+use crate::analysis::errors::{emit_error, MacroError};
+
+/// Macros whose arguments are expressions, e.g. `println!("{}", self.ledger.balance())`.
+/// Role method calls in their arguments are rewritten like anywhere else in a method body
+const EXPRESSION_MACROS: &[&str] = &[
+    "assert",
+    "assert_eq",
+    "assert_ne",
+    "dbg",
+    "debug_assert",
+    "debug_assert_eq",
+    "debug_assert_ne",
+    "eprint",
+    "eprintln",
+    "format",
+    "format_args",
+    "matches",
+    "panic",
+    "print",
+    "println",
+    "todo",
+    "unimplemented",
+    "unreachable",
+    "vec",
+    "write",
+    "writeln",
+];
+
+/// The arguments of an expression-like macro
+enum MacroArgs {
+    /// `format!("{}", a, b)`, `assert_eq!(a, b)`, `vec![a, b]`
+    List(Punctuated<Expr, Token![,]>),
+    /// `vec![a; n]`
+    Repeat(Box<Expr>, Token![;], Box<Expr>),
+    /// `matches!(a, Pattern if guard)`
+    Matches {
+        expr: Box<Expr>,
+        comma: Token![,],
+        pat: Box<syn::Pat>,
+        guard: Option<(Token![if], Box<Expr>)>,
+        trailing: Option<Token![,]>,
+    },
+}
+
+impl MacroArgs {
+    fn parse(tokens: TokenStream) -> Option<Self> {
+        if let Ok(list) = Punctuated::<Expr, Token![,]>::parse_terminated.parse2(tokens.clone()) {
+            return Some(MacroArgs::List(list));
+        }
+        let repeat = |input: syn::parse::ParseStream| {
+            Ok(MacroArgs::Repeat(input.parse()?, input.parse()?, input.parse()?))
+        };
+        if let Ok(repeat) = repeat.parse2(tokens.clone()) {
+            return Some(repeat);
+        }
+        let matches = |input: syn::parse::ParseStream| {
+            let expr = input.parse()?;
+            let comma = input.parse()?;
+            let pat = Box::new(syn::Pat::parse_multi_with_leading_vert(input)?);
+            let guard = match input.parse::<Option<Token![if]>>()? {
+                Some(if_token) => Some((if_token, input.parse()?)),
+                None => None,
+            };
+            Ok(MacroArgs::Matches {
+                expr,
+                comma,
+                pat,
+                guard,
+                trailing: input.parse()?,
+            })
+        };
+        matches.parse2(tokens).ok()
+    }
+
+    fn exprs_mut(&mut self) -> Vec<&mut Expr> {
+        match self {
+            MacroArgs::List(list) => list.iter_mut().collect(),
+            MacroArgs::Repeat(elem, _, len) => vec![elem, len],
+            MacroArgs::Matches { expr, guard, .. } => std::iter::once(&mut **expr)
+                .chain(guard.as_mut().map(|(_, guard)| &mut **guard))
+                .collect(),
+        }
+    }
+}
+
+impl ToTokens for MacroArgs {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            MacroArgs::List(list) => list.to_tokens(tokens),
+            MacroArgs::Repeat(elem, semi, len) => {
+                elem.to_tokens(tokens);
+                semi.to_tokens(tokens);
+                len.to_tokens(tokens);
+            }
+            MacroArgs::Matches {
+                expr,
+                comma,
+                pat,
+                guard,
+                trailing,
+            } => {
+                expr.to_tokens(tokens);
+                comma.to_tokens(tokens);
+                pat.to_tokens(tokens);
+                if let Some((if_token, guard)) = guard {
+                    if_token.to_tokens(tokens);
+                    guard.to_tokens(tokens);
+                }
+                trailing.to_tokens(tokens);
+            }
+        }
+    }
+}
+
+fn mentions_self(tokens: &TokenStream) -> bool {
+    tokens.clone().into_iter().any(|tree| match tree {
+        TokenTree::Ident(ident) => ident == "self",
+        TokenTree::Group(group) => mentions_self(&group.stream()),
+        _ => false,
+    })
+}
+
+/// Whether the arguments of the macro are expressions evaluated where it's invoked
+pub(super) fn is_expression_macro(mac: &syn::Macro) -> bool {
+    mac.path
+        .segments
+        .last()
+        .is_some_and(|segment| EXPRESSION_MACROS.contains(&segment.ident.to_string().as_str()))
+}
+
+/// Applies `visitor` to the arguments of a macro invocation.
+///
+/// The arguments of well-known expression-like macros are parsed, rewritten and put back.
+/// Other macros are left untouched, but if their arguments would have been rewritten, or
+/// can't be parsed and mention `self`, an error is reported, because a role method call
+/// would otherwise silently be made on the player.
+pub(super) fn rewrite_macro<V: VisitMut>(visitor: &mut V, mac: &mut syn::Macro) {
+    let name = mac
+        .path
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+        .unwrap_or_default();
+    let known = EXPRESSION_MACROS.contains(&name.as_str());
+
+    match MacroArgs::parse(mac.tokens.clone()) {
+        Some(mut args) => {
+            for expr in args.exprs_mut() {
+                visitor.visit_expr_mut(expr);
+            }
+            let rewritten = args.to_token_stream();
+            if known {
+                mac.tokens = rewritten;
+            } else if rewritten.to_string() != mac.tokens.to_string() {
+                emit_error(MacroError::new(
+                    format!(
+                        "role methods and `self` cannot be rewritten inside `{}!`; \
+                         bind the value to a local variable outside the macro and use that instead",
+                        name
+                    ),
+                    mac.path.segments.last().unwrap().ident.span(),
+                ));
+            }
+        }
+        // Whether a role method is called can't be told, `self` alone is reason enough
+        None if mentions_self(&mac.tokens) => {
+            emit_error(MacroError::new(
+                format!(
+                    "the arguments of `{}!` could not be parsed as expressions, so role methods \
+                     and `self` inside it cannot be rewritten; bind the value to a local \
+                     variable outside the macro and use that instead",
+                    name
+                ),
+                mac.path.segments.last().unwrap().ident.span(),
+            ));
+        }
+        None => (),
+    }
+}
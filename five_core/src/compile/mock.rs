@@ -0,0 +1,269 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::visit_mut::VisitMut;
+
+use crate::analysis::errors::{emit_error, MacroError};
+use crate::analysis::mock_name;
+
+/// A contract method the mock implements
+struct MockedMethod<'a> {
+    method: &'a syn::TraitItemFn,
+    /// The names the arguments are bound to in the mock
+    args: Vec<syn::Ident>,
+    /// The type of the handler computing the result, e.g. `dyn FnMut(&str) -> u32 + Send`
+    handler: syn::Type,
+}
+
+/// `Mock<Contract>`, implementing the contract by recording every call and answering it
+/// with the handler given for the method. Methods with a default body keep it.
+pub fn compile_mock(contract: &syn::ItemTrait, five: &syn::Path) -> TokenStream {
+    let methods: Vec<MockedMethod> = contract_methods(contract, "mocked").into_iter().map(mocked_method).collect();
+    check_mock_names(contract, &methods);
+
+    let contract_name = &contract.ident;
+    let mock = mock_name(contract_name);
+    let vis = &contract.vis;
+    let names: Vec<&syn::Ident> = methods.iter().map(|method| &method.method.sig.ident).collect();
+    let handlers: Vec<&syn::Type> = methods.iter().map(|method| &method.handler).collect();
+    let expect_fns = names.iter().map(|name| format_ident!("expect_{}", name));
+    let labels: Vec<String> = names.iter().map(|name| format!("{}::{}", contract_name, name)).collect();
+
+    // Locals of the generated methods, out of reach of the arguments
+    let expectation = syn::Ident::new("expectation", Span::mixed_site());
+    let handler = syn::Ident::new("handler", Span::mixed_site());
+    let impl_methods = methods.iter().map(|mocked| {
+        let method = mocked.method;
+        let name = &method.sig.ident;
+        let label = format!("{}::{}", contract_name, name);
+        let args = &mocked.args;
+
+        let sig = bound_signature(&method.sig, args);
+
+        let result = if matches!(&sig.output, syn::ReturnType::Default) {
+            // Calls without a result are fine without a handler
+            quote! {
+                if let ::core::option::Option::Some(#handler) = #expectation.call(#label) {
+                    #handler(#(#args),*)
+                }
+            }
+        } else {
+            let missing = format!("{} was called without a handler, give one with `expect_{}().returning(..)`", label, name);
+            quote! {
+                match #expectation.call(#label) {
+                    ::core::option::Option::Some(#handler) => #handler(#(#args),*),
+                    ::core::option::Option::None => ::core::panic!(#missing),
+                }
+            }
+        };
+        let method_name = name.to_string();
+        quote! {
+            #sig {
+                use #five::mock::{DebugArg as _, OpaqueArg as _};
+                self.__five_calls.record(#method_name, &[#((&#five::mock::Arg(&#args)).describe()),*]);
+                let mut #expectation = self.#name.lock().unwrap_or_else(|err| err.into_inner());
+                #result
+            }
+        }
+    });
+
+    let async_trait = async_trait_attr(contract);
+
+    let doc = format!(
+        "A mock of [`{}`] recording its calls and answering them with the handlers given \
+         with `expect_<method>().returning(..)`",
+        contract_name
+    );
+    quote! {
+        #[doc = #doc]
+        #[derive(Default)]
+        #vis struct #mock {
+            #(#names: #five::mock::Mutex<#five::mock::Expectation<#handlers>>,)*
+            __five_calls: #five::mock::Calls,
+        }
+
+        impl #mock {
+            pub fn new() -> Self {
+                ::core::default::Default::default()
+            }
+
+            #(
+                pub fn #expect_fns(&mut self) -> &mut #five::mock::Expectation<#handlers> {
+                    self.#names.get_mut().unwrap_or_else(|err| err.into_inner())
+                }
+            )*
+
+            /// The calls made so far, in order, e.g. `push(Deposit("Salary", 100))`
+            pub fn calls(&self) -> #five::mock::Vec<#five::mock::String> {
+                self.__five_calls.get()
+            }
+
+            /// Panics unless every method was called as often as expected
+            pub fn verify(&self) {
+                #(self.#names.lock().unwrap_or_else(|err| err.into_inner()).verify(#labels);)*
+            }
+        }
+
+        impl ::core::ops::Drop for #mock {
+            fn drop(&mut self) {
+                if !#five::mock::panicking() {
+                    self.verify();
+                }
+            }
+        }
+
+        #async_trait
+        impl #contract_name for #mock {
+            #(#impl_methods)*
+        }
+    }
+}
+
+/// Reports contract methods named like the inherent methods of the mock, which would
+/// otherwise shadow them when called on the mock
+fn check_mock_names(contract: &syn::ItemTrait, methods: &[MockedMethod]) {
+    let mock = mock_name(&contract.ident);
+    let mut inherent: Vec<String> = ["new", "calls", "verify"].map(String::from).to_vec();
+    inherent.extend(methods.iter().map(|mocked| format!("expect_{}", mocked.method.sig.ident)));
+    let contract_methods = contract.items.iter().filter_map(|item| match item {
+        syn::TraitItem::Fn(method) => Some(&method.sig.ident),
+        _ => None,
+    });
+    for name in contract_methods {
+        if inherent.contains(&name.to_string()) {
+            emit_error(MacroError::new(
+                format!("`{}` is a method of the generated `{}`, rename this contract method", name, mock),
+                name.span(),
+            ));
+        }
+    }
+}
+
+fn mocked_method(method: &syn::TraitItemFn) -> MockedMethod<'_> {
+    let sig = &method.sig;
+    let args = arg_names(sig);
+
+    // Borrowed arguments are borrowed for the call only, borrowed results are `'static`
+    let arg_types = sig.inputs.iter().filter_map(|input| match input {
+        syn::FnArg::Typed(pat_type) => {
+            let mut ty = (*pat_type.ty).clone();
+            Lifetimes(None).visit_type_mut(&mut ty);
+            Some(ty)
+        }
+        syn::FnArg::Receiver(_) => None,
+    });
+    let output = match &sig.output {
+        syn::ReturnType::Default => quote!(),
+        syn::ReturnType::Type(_, ty) => {
+            let mut ty = (**ty).clone();
+            Lifetimes(Some(syn::parse_quote!('static))).visit_type_mut(&mut ty);
+            quote!(-> #ty)
+        }
+    };
+    let handler = syn::parse_quote!(dyn ::core::ops::FnMut(#(#arg_types),*) #output + ::core::marker::Send);
+
+    MockedMethod { method, args, handler }
+}
+
+/// The methods of a contract a generated player implements, those without a default body.
+/// Reports what a generated player can't implement, `action` saying what it's generated
+/// for, e.g. "mocked".
+pub(super) fn contract_methods<'a>(contract: &'a syn::ItemTrait, action: &str) -> Vec<&'a syn::TraitItemFn> {
+    if !contract.generics.params.is_empty() {
+        emit_error(MacroError::new(
+            format!("contracts with generic parameters can't be {}", action),
+            param_span(&contract.generics.params[0]),
+        ));
+    }
+
+    let mut methods = vec![];
+    for item in &contract.items {
+        match item {
+            syn::TraitItem::Fn(method) if method.default.is_none() => {
+                let sig = &method.sig;
+                if sig.receiver().is_none() {
+                    emit_error(MacroError::new(
+                        format!("contract methods without `self` can't be {}, give them a default body", action),
+                        sig.ident.span(),
+                    ));
+                } else if let Some(param) = sig.generics.type_params().next() {
+                    emit_error(MacroError::new(
+                        format!("generic contract methods can't be {}, give them a default body", action),
+                        param.ident.span(),
+                    ));
+                } else {
+                    methods.push(method);
+                }
+            }
+            syn::TraitItem::Fn(_) => (),
+            syn::TraitItem::Type(item) => emit_error(MacroError::new(
+                format!("contracts with associated types can't be {}", action),
+                item.ident.span(),
+            )),
+            syn::TraitItem::Const(item) if item.default.is_none() => emit_error(MacroError::new(
+                format!("contracts with associated constants can't be {}", action),
+                item.ident.span(),
+            )),
+            _ => (),
+        }
+    }
+    methods
+}
+
+/// The names the arguments of a contract method are bound to in a generated player
+pub(super) fn arg_names(sig: &syn::Signature) -> Vec<syn::Ident> {
+    sig.inputs
+        .iter()
+        .filter_map(|input| match input {
+            syn::FnArg::Typed(pat_type) => Some(pat_type),
+            syn::FnArg::Receiver(_) => None,
+        })
+        .enumerate()
+        .map(|(index, pat_type)| match &*pat_type.pat {
+            syn::Pat::Ident(pat) => pat.ident.clone(),
+            _ => format_ident!("arg{}", index),
+        })
+        .collect()
+}
+
+/// The signature as declared, with the arguments bound to `args`
+pub(super) fn bound_signature(sig: &syn::Signature, args: &[syn::Ident]) -> syn::Signature {
+    let mut sig = sig.clone();
+    let inputs = sig.inputs.iter_mut().filter_map(|input| match input {
+        syn::FnArg::Typed(pat_type) => Some(pat_type),
+        syn::FnArg::Receiver(_) => None,
+    });
+    for (input, arg) in inputs.zip(args) {
+        *input.pat = syn::parse_quote!(#arg);
+    }
+    sig
+}
+
+/// The `#[async_trait]` attribute of a contract, which its generated players need as well
+pub(super) fn async_trait_attr(contract: &syn::ItemTrait) -> Option<&syn::Attribute> {
+    contract
+        .attrs
+        .iter()
+        .find(|attr| attr.path().segments.last().is_some_and(|segment| segment.ident == "async_trait"))
+}
+
+fn param_span(param: &syn::GenericParam) -> proc_macro2::Span {
+    match param {
+        syn::GenericParam::Type(param) => param.ident.span(),
+        syn::GenericParam::Lifetime(param) => param.lifetime.span(),
+        syn::GenericParam::Const(param) => param.ident.span(),
+    }
+}
+
+/// Replaces the lifetimes of a type with `'static`, or elides them
+struct Lifetimes(Option<syn::Lifetime>);
+
+impl VisitMut for Lifetimes {
+    fn visit_type_reference_mut(&mut self, reference: &mut syn::TypeReference) {
+        reference.lifetime = self.0.clone();
+        syn::visit_mut::visit_type_reference_mut(self, reference);
+    }
+
+    fn visit_lifetime_mut(&mut self, lifetime: &mut syn::Lifetime) {
+        *lifetime = self.0.clone().unwrap_or_else(|| syn::parse_quote!('_));
+    }
+}
@@ -1,9 +1,16 @@
 mod compiler;
 mod context;
+mod descriptor;
 mod function_descriptor;
 mod impl_block;
+mod intercept;
+mod macro_args;
+pub mod mock;
 pub mod module;
+mod provides;
+pub mod record;
 mod role;
+mod trace;
 mod trait_info;
 
 pub use compiler::*;
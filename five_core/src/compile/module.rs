@@ -1,58 +1,45 @@
+use proc_macro2::Span;
+
 use crate::analysis::{
-    FunctionDescription, GenericsInfo, ModuleInfo, ParameterInfo, TypeDescription,
+    bind_name, context_trait_name, descriptor_name, is_phantom_field, FunctionDescription, GenericsInfo, ModuleInfo,
+    ParameterInfo, ProvidedContract, TypeDescription,
 };
 
-use super::{context::CompiledContext, Compiled, Compiler};
+use super::{context::CompiledContext, descriptor::compile_descriptor, trace::trace_context, Compiled, Compiler};
 
 #[derive(Clone)]
 pub struct CompiledModule {
     pub module_name: syn::Ident,
+    pub vis: syn::Visibility,
+    pub attrs: Vec<syn::Attribute>,
     pub context: CompiledContext,
     pub others: Vec<syn::Item>,
+    pub provides: Vec<ProvidedContract>,
+    pub descriptor: syn::ItemConst,
+    pub crate_path: syn::Path,
 }
 
 impl Compiled<ModuleInfo> for CompiledModule {
     fn emit(&self) -> proc_macro2::TokenStream {
-        use quote::quote;
+        use quote::{quote, quote_spanned};
 
         let module_name = &self.module_name;
         let mut context = self.context.clone();
 
-        // Create PascalCase trait name from module name
-        let module_str = module_name.to_string();
-        
-        // Convert snake_case to PascalCase
-        let name = module_str.split('_')
-            .map(|part| {
-                if part.is_empty() {
-                    String::new()
-                } else {
-                    let mut chars = part.chars();
-                    match chars.next() {
-                        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
-                        None => String::new(),
-                    }
-                }
-            })
-            .collect::<String>();
-            
-        let trait_name = syn::Ident::new(
-            &name,
-            module_name.span()
-        );
+        let trait_name = context_trait_name(module_name);
 
         // Update the trait name to match the module name in PascalCase
         context.context_trait.ident = trait_name.clone();
 
-        // Get generics from the context base struct
+        // Get the name and generics from the context base struct
+        let context_name = &context.base.ident;
         let (_impl_generics, ty_generics, _where_clausee) = context.base.generics.split_for_impl();
 
         // Update the impl blocks to implement the renamed trait with generics
         for impl_block in &mut context.context_methods {
             impl_block.implemented_traits = vec![syn::parse_quote!(#trait_name #ty_generics)];
-            impl_block.self_ty = syn::parse_quote!(Context #ty_generics);
+            impl_block.self_ty = syn::parse_quote!(#context_name #ty_generics);
             impl_block.generics = GenericsInfo::from_syn_generics(&context.base.generics);
-            impl_block.attrs = context.attrs.clone();
         }
         
 
@@ -69,7 +56,7 @@ impl Compiled<ModuleInfo> for CompiledModule {
         let field_names = field_names.collect::<Vec<_>>();
         let field_types = field_types.collect::<Vec<_>>();
 
-        let bind_fn_name = syn::Ident::new("bind", proc_macro2::Span::call_site());
+        let bind_fn_name = bind_name(module_name);
 
         let context_type = syn::Type::Path(syn::TypePath {
             qself: None,
@@ -78,11 +65,11 @@ impl Compiled<ModuleInfo> for CompiledModule {
                 segments: {
                     let mut segments = syn::punctuated::Punctuated::new();
                     segments.push(syn::PathSegment {
-                        ident: syn::Ident::new("Context", proc_macro2::Span::call_site()),
+                        ident: context_name.clone(),
                         arguments: syn::PathArguments::AngleBracketed(
                             syn::AngleBracketedGenericArguments {
                                 colon2_token: None,
-                                lt_token: syn::Token![<](proc_macro2::Span::call_site()),
+                                lt_token: syn::Token![<](context_name.span()),
                                 args: context
                                     .base
                                     .generics
@@ -107,7 +94,7 @@ impl Compiled<ModuleInfo> for CompiledModule {
                                         ),
                                     })
                                     .collect(),
-                                gt_token: syn::Token![>](proc_macro2::Span::call_site()),
+                                gt_token: syn::Token![>](context_name.span()),
                             },
                         ),
                     });
@@ -132,12 +119,11 @@ impl Compiled<ModuleInfo> for CompiledModule {
                         
                         // Add all non-phantom fields from parameters
                         for field_name in field_names.iter() {
-                            let field_name_str = field_name.as_ref().unwrap().to_string();
-                            if !field_name_str.starts_with("_phantom_") {
+                            if !is_phantom_field(field_name.as_ref().unwrap()) {
                                 fields.push(syn::FieldValue {
                                     attrs: vec![],
                                     member: syn::Member::Named(field_name.as_ref().unwrap().clone()),
-                                    colon_token: Some(syn::Token![:](proc_macro2::Span::call_site())),
+                                    colon_token: Some(syn::Token![:](field_name.as_ref().unwrap().span())),
                                     expr: syn::Expr::Path(syn::ExprPath {
                                         attrs: vec![],
                                         qself: None,
@@ -149,13 +135,12 @@ impl Compiled<ModuleInfo> for CompiledModule {
                         
                         // Add default PhantomData for phantom fields
                         for field_name in field_names.iter() {
-                            let field_name_str = field_name.as_ref().unwrap().to_string();
-                            if field_name_str.starts_with("_phantom_") {
+                            if is_phantom_field(field_name.as_ref().unwrap()) {
                                 fields.push(syn::FieldValue {
                                     attrs: vec![],
                                     member: syn::Member::Named(field_name.as_ref().unwrap().clone()),
-                                    colon_token: Some(syn::Token![:](proc_macro2::Span::call_site())),
-                                    expr: syn::parse_quote!(::std::marker::PhantomData),
+                                    colon_token: Some(syn::Token![:](field_name.as_ref().unwrap().span())),
+                                    expr: syn::parse_quote!(::core::marker::PhantomData),
                                 });
                             }
                         }
@@ -181,8 +166,7 @@ impl Compiled<ModuleInfo> for CompiledModule {
             .zip(field_types.iter())
             .filter(|(name, _)| {
                 // Filter out self and _phantom_ parameters
-                let name_str = name.as_ref().unwrap().to_string();
-                *name.as_ref().unwrap() != "self" && !name_str.starts_with("_phantom_")
+                *name.as_ref().unwrap() != "self" && !is_phantom_field(name.as_ref().unwrap())
             })
             .map(|(name, ty)| {
                 let param = ParameterInfo::Typed {
@@ -207,34 +191,83 @@ impl Compiled<ModuleInfo> for CompiledModule {
         let bind_fn = bind_fn.compile();
 
         let bind_fn = bind_fn.emit();
+
+        // Forwarding impls for the contracts of other contexts this context can play a role in
+        let provides = self
+            .provides
+            .iter()
+            .map(|provided| provided.compile(&trait_name, &context.base).emit());
+
+        // Bound contexts are described through the context trait, which requires `Describe`
+        let descriptor = &self.descriptor;
+        let descriptor_name = descriptor_name(module_name);
+        let (impl_generics, ty_generics, where_clause) = context.base.generics.split_for_impl();
+        let five = &self.crate_path;
+        let describe = quote! {
+            impl #impl_generics #five::Describe for #context_name #ty_generics #where_clause {
+                fn descriptor(&self) -> &'static #five::ContextDescriptor {
+                    &#descriptor_name
+                }
+            }
+        };
+
         let context = context.emit();
         let others = &self.others;
 
-        
-        quote! {
-            #context
-            #bind_fn
-            #(#others)*
+        // The module keeps its items to itself but sees everything in scope around it, as if
+        // it were written inline. Its public items are re-exported, so the context is used
+        // through the enclosing module, e.g. `account::bind`. The wrapper is hygienic, it
+        // isn't the user's code
+        let vis = &self.vis;
+        let attrs = &self.attrs;
+        quote_spanned! {Span::mixed_site()=>
+            #(#attrs)*
+            #vis mod #module_name {
+                use super::*;
+
+                #context
+                #bind_fn
+                #descriptor
+                #describe
+                #(#provides)*
+                #(#others)*
+            }
+            #vis use self::#module_name::*;
         }
     }
 }
 
 impl Compiler<ModuleInfo> for ModuleInfo {
     fn compile(&self) -> CompiledModule {
-        // Compile the context first
-        let compiled_context = self.context.compile();
+        // Compile the context first, instrumented with `#[five::context(trace)]`
+        let compiled_context = if self.attributes.trace {
+            trace_context(&self.module_name, &self.context).compile()
+        } else {
+            self.context.compile()
+        };
 
         CompiledModule {
             module_name: self.module_name.clone(),
+            vis: self.vis.clone(),
+            attrs: self.attrs.clone(),
             context: compiled_context,
             others: self
                 .others
                 .iter()
                 .filter_map(|item| match item {
                     TypeDescription::Other(item) => Some(item.clone()),
+                    // Contracts not tied to a role by name are still part of the context's API
+                    TypeDescription::RoleContract(contract) => {
+                        let mut contract = contract.compile().trait_item;
+                        contract.vis = syn::Visibility::Public(syn::token::Pub::default());
+                        Some(syn::Item::Trait(contract))
+                    }
                     _ => None,
                 })
                 .collect(), // Just clone the others without compilation
+            provides: self.attributes.provides.clone(),
+            descriptor: compile_descriptor(self),
+            crate_path: self.attributes.crate_path(),
         }
     }
 
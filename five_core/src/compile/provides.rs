@@ -0,0 +1,173 @@
+use quote::{quote, ToTokens};
+use syn::{visit_mut::VisitMut, Ident, ItemStruct};
+
+use crate::analysis::{is_phantom_field, to_snake_case};
+use super::Compiled;
+use crate::analysis::ProvidedContract;
+
+/// The forwarding impl of a contract provided by the context, together with a binder that
+/// returns the context typed as a player of that contract
+#[derive(Clone)]
+pub struct CompiledProvidedContract {
+    pub item_impl: syn::ItemImpl,
+    pub bind_fn: syn::ItemFn,
+}
+
+impl Compiled<ProvidedContract> for CompiledProvidedContract {
+    fn emit(&self) -> proc_macro2::TokenStream {
+        let item_impl = &self.item_impl;
+        let bind_fn = &self.bind_fn;
+        quote! {
+            #item_impl
+            #bind_fn
+        }
+    }
+}
+
+/// Replaces the generic parameters fixed by a `where T = Type` clause with the given type
+struct GenericSubstitution<'a> {
+    substitutions: &'a [(Ident, syn::Type)],
+}
+
+impl VisitMut for GenericSubstitution<'_> {
+    fn visit_type_mut(&mut self, ty: &mut syn::Type) {
+        if let syn::Type::Path(type_path) = ty {
+            if type_path.qself.is_none() {
+                if let Some(ident) = type_path.path.get_ident() {
+                    if let Some((_, replacement)) =
+                        self.substitutions.iter().find(|(generic, _)| generic == ident)
+                    {
+                        *ty = replacement.clone();
+                        return;
+                    }
+                }
+            }
+        }
+        syn::visit_mut::visit_type_mut(self, ty);
+    }
+}
+
+impl ProvidedContract {
+    /// Name of the binder for this contract, e.g. `bind_user_storage` for `UserStorageContract`
+    fn bind_fn_name(&self) -> Ident {
+        let contract = &self.contract.segments.last().unwrap().ident;
+        let name = to_snake_case(&contract.to_string());
+        let name = name.strip_suffix("_contract").unwrap_or(&name);
+        Ident::new(&format!("bind_{}", name), contract.span())
+    }
+
+    pub fn compile(&self, context_trait: &Ident, base: &ItemStruct) -> CompiledProvidedContract {
+        let mut substitution = GenericSubstitution {
+            substitutions: &self.substitutions,
+        };
+
+        // The generic arguments of the context with the fixed generics substituted
+        let context_args: Vec<proc_macro2::TokenStream> = base
+            .generics
+            .params
+            .iter()
+            .map(|param| match param {
+                syn::GenericParam::Type(tp) => {
+                    match self.substitutions.iter().find(|(generic, _)| *generic == tp.ident) {
+                        Some((_, ty)) => ty.to_token_stream(),
+                        None => tp.ident.to_token_stream(),
+                    }
+                }
+                syn::GenericParam::Lifetime(l) => l.lifetime.to_token_stream(),
+                syn::GenericParam::Const(c) => c.ident.to_token_stream(),
+            })
+            .collect();
+
+        // The generics that remain open, with the substitution applied to their bounds
+        let mut generics = base.generics.clone();
+        generics.params = generics
+            .params
+            .into_iter()
+            .filter(|param| match param {
+                syn::GenericParam::Type(tp) => {
+                    !self.substitutions.iter().any(|(generic, _)| *generic == tp.ident)
+                }
+                _ => true,
+            })
+            .collect();
+        substitution.visit_generics_mut(&mut generics);
+
+        // Extra bounds on a generic parameter go inline, anything else into the where clause
+        for predicate in &self.predicates {
+            if let syn::WherePredicate::Type(predicate_type) = predicate {
+                let param = match &predicate_type.bounded_ty {
+                    syn::Type::Path(type_path) if predicate_type.lifetimes.is_none() => type_path
+                        .path
+                        .get_ident()
+                        .and_then(|ident| generics.type_params_mut().find(|tp| tp.ident == *ident)),
+                    _ => None,
+                };
+                if let Some(param) = param {
+                    param.colon_token.get_or_insert_with(Default::default);
+                    param.bounds.extend(predicate_type.bounds.iter().cloned());
+                    continue;
+                }
+            }
+            generics.make_where_clause().predicates.push(predicate.clone());
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+        let contract = &self.contract;
+        let attrs = &self.attrs;
+        let methods = self.methods.iter().map(|method| {
+            let signature = &method.signature;
+            let interaction = &method.interaction;
+            let args = method.args.iter();
+            let call = quote! {
+                <Self as #context_trait<#(#context_args),*>>::#interaction(self, #(#args),*)
+            };
+            let call = if signature.asyncness.is_some() {
+                quote!(#call.await)
+            } else {
+                call
+            };
+            quote! {
+                #signature {
+                    #call
+                }
+            }
+        });
+        let item_impl = syn::parse_quote! {
+            #(#attrs)*
+            impl #impl_generics #contract for Context<#(#context_args),*> #where_clause {
+                #(#methods)*
+            }
+        };
+
+        // The binder takes the same players and data as `bind`
+        let fields: Vec<&syn::Field> = base
+            .fields
+            .iter()
+            .filter(|field| !is_phantom_field(field.ident.as_ref().unwrap()))
+            .collect();
+        let params = fields.iter().map(|field| {
+            let name = &field.ident;
+            let mut ty = field.ty.clone();
+            substitution.visit_type_mut(&mut ty);
+            quote!(#name: #ty)
+        });
+        let field_values = base.fields.iter().map(|field| {
+            let name = &field.ident;
+            if is_phantom_field(name.as_ref().unwrap()) {
+                quote!(#name: ::core::marker::PhantomData)
+            } else {
+                quote!(#name)
+            }
+        });
+        let bind_fn_name = self.bind_fn_name();
+        let bind_fn = syn::parse_quote! {
+            pub fn #bind_fn_name #impl_generics (#(#params),*) -> impl #context_trait<#(#context_args),*> + #contract #where_clause {
+                Context {
+                    #(#field_values),*
+                }
+            }
+        };
+
+        CompiledProvidedContract { item_impl, bind_fn }
+    }
+}
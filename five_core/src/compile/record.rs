@@ -0,0 +1,120 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::visit::Visit;
+
+use super::mock::{arg_names, async_trait_attr, bound_signature, contract_methods};
+use crate::analysis::errors::{emit_error, MacroError};
+use crate::analysis::{recording_name, replaying_name};
+
+/// `Recording<Contract>`, forwarding the calls to a real player and recording them with
+/// their results, and `Replaying<Contract>`, answering the calls from a recorded transcript
+pub fn compile_record(contract: &syn::ItemTrait, five: &syn::Path) -> TokenStream {
+    let methods = contract_methods(contract, "recorded");
+    for method in &methods {
+        if let syn::ReturnType::Type(_, ty) = &method.sig.output {
+            let mut borrows = Borrows(false);
+            borrows.visit_type(ty);
+            if borrows.0 {
+                emit_error(MacroError::new(
+                    "contract methods returning borrowed values can't be replayed, give them a default body",
+                    method.sig.ident.span(),
+                ));
+            }
+        }
+    }
+
+    let contract_name = &contract.ident;
+    let recording = recording_name(contract_name);
+    let replaying = replaying_name(contract_name);
+    let vis = &contract.vis;
+    let label = contract_name.to_string();
+
+    let (recorded, replayed): (Vec<TokenStream>, Vec<TokenStream>) = methods
+        .iter()
+        .map(|method| {
+            let name = &method.sig.ident;
+            let method_name = name.to_string();
+            let args = arg_names(&method.sig);
+            let sig = bound_signature(&method.sig, &args);
+            let await_call = sig.asyncness.map(|_| quote!(.await));
+            let recorded = quote! {
+                #sig {
+                    let args = #five::record::vec![#(#five::record::to_value(&#args)),*];
+                    let result = self.player.#name(#(#args),*) #await_call;
+                    self.recorder.record(#label, #method_name, args, #five::record::to_value(&result));
+                    result
+                }
+            };
+            let replayed = quote! {
+                #sig {
+                    let args = #five::record::vec![#(#five::record::to_value(&#args)),*];
+                    #five::record::from_value(self.replayer.replay(#label, #method_name, args))
+                }
+            };
+            (recorded, replayed)
+        })
+        .unzip();
+
+    let async_trait = async_trait_attr(contract);
+    let recording_doc = format!(
+        "A player of [`{}`] forwarding every call to another player and recording it",
+        contract_name
+    );
+    let replaying_doc = format!("A player of [`{}`] answering every call from a transcript", contract_name);
+    quote! {
+        #[doc = #recording_doc]
+        #vis struct #recording<TPlayer> {
+            player: TPlayer,
+            recorder: #five::record::Recorder,
+        }
+
+        impl<TPlayer> #recording<TPlayer> {
+            pub fn new(player: TPlayer, recorder: &#five::record::Recorder) -> Self {
+                #recording {
+                    player,
+                    recorder: ::core::clone::Clone::clone(recorder),
+                }
+            }
+
+            pub fn into_inner(self) -> TPlayer {
+                self.player
+            }
+        }
+
+        #async_trait
+        impl<TPlayer: #contract_name> #contract_name for #recording<TPlayer> {
+            #(#recorded)*
+        }
+
+        #[doc = #replaying_doc]
+        #vis struct #replaying {
+            replayer: #five::record::Replayer,
+        }
+
+        impl #replaying {
+            pub fn new(replayer: &#five::record::Replayer) -> Self {
+                #replaying {
+                    replayer: ::core::clone::Clone::clone(replayer),
+                }
+            }
+        }
+
+        #async_trait
+        impl #contract_name for #replaying {
+            #(#replayed)*
+        }
+    }
+}
+
+/// Finds references and lifetimes in a type
+struct Borrows(bool);
+
+impl Visit<'_> for Borrows {
+    fn visit_type_reference(&mut self, _: &syn::TypeReference) {
+        self.0 = true;
+    }
+
+    fn visit_lifetime(&mut self, _: &syn::Lifetime) {
+        self.0 = true;
+    }
+}
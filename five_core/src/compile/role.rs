@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+use quote::ToTokens;
+use syn::{visit_mut::VisitMut, Expr, Member};
+
+use super::context::rewrite_role_access;
+use super::function_descriptor::trait_method;
+use super::macro_args::rewrite_macro;
+use super::{Compiled, CompiledImplBlock, CompiledTraitInfo, Compiler};
+use crate::analysis::{
+    to_role_name, AsyncStrategy, GenericsInfo, FunctionDescription, ImplBlockInfo, ParameterInfo, Role, RoleMethodBorrows, SelfType,
+};
+use crate::analysis::role_generic_name;
+
+#[derive(Clone)]
+pub struct CompiledRole {
+    pub role_trait: syn::ItemTrait,
+    pub impl_block: CompiledImplBlock,
+    pub contract: Option<CompiledTraitInfo>,
+}
+
+impl Compiled<Role> for CompiledRole {
+    fn emit(&self) -> proc_macro2::TokenStream {
+        use quote::quote;
+
+        let role_trait = &self.role_trait;
+        let impl_block = self.impl_block.emit();
+        let contract = &self.contract;
+
+        quote! {
+            #contract
+
+            #role_trait
+
+            #impl_block
+        }
+    }
+}
+
+/// Where a role method is called from
+#[derive(Clone)]
+pub(super) enum Caller {
+    /// A function on the context taking `self` as given, `None` for associated functions
+    Context(Option<SelfType>),
+    /// Like `Context`, calling the role method on a local alias of the player,
+    /// `let ledger = &mut self.ledger; ledger.add(entry)`. The expression is the player
+    /// place the alias gives access to, e.g. `*ledger`
+    Alias(Box<Expr>, Option<SelfType>),
+    /// A role method that only borrows its player and data fields
+    Player,
+}
+
+/// The name a role method borrowing its player and data fields gives to the borrows.
+/// The identifiers are hygienic, so they never clash with names in the user's code
+fn borrow_ident(name: &str) -> syn::Ident {
+    syn::Ident::new(name, proc_macro2::Span::mixed_site())
+}
+
+/// Calls the role method `method` through the role's trait on the context, e.g.
+/// `<Self as LedgerRole<TLedger>>::add(&mut *self, entry)`.
+///
+/// The call is dispatched by the role trait rather than by name, so role methods can share
+/// their names with interactions and with methods of other roles. Role methods that only
+/// borrow their player and data fields are passed just those,
+/// `<Self as LedgerRole<TLedger>>::add(&mut self.ledger, &self.account_no, entry)`.
+pub(super) fn role_method_call(
+    role: &Role,
+    method: &syn::Ident,
+    turbofish: Option<&syn::AngleBracketedGenericArguments>,
+    args: syn::punctuated::Punctuated<Expr, syn::Token![,]>,
+    context_generics: &syn::Generics,
+    caller: Caller,
+) -> Expr {
+    use quote::quote_spanned;
+
+    // The generated parts of the call point at the method name, so borrow errors
+    // are reported on the user's call
+    let span = method.span();
+    let role_trait = &role.name;
+    let (_, ty_generics, _) = context_generics.split_for_impl();
+    let borrows = match role.borrows(method) {
+        RoleMethodBorrows::Fields { player, fields } => {
+            let player = (
+                syn::Ident::new(&to_role_name(&role.name.to_string()), span),
+                *player == SelfType::MutableReference,
+            );
+            let fields = fields.iter().map(|field| (field.name.clone(), field.mutable));
+            std::iter::once(player)
+                .chain(fields)
+                .enumerate()
+                .map(|(i, (name, mutable))| {
+                    let mutability = mutable.then_some(<syn::Token![mut]>::default());
+                    match &caller {
+                        Caller::Alias(player, _) if i == 0 => quote_spanned!(span=> &#mutability #player),
+                        Caller::Context(_) | Caller::Alias(..) => quote_spanned!(span=> &#mutability self.#name),
+                        Caller::Player => {
+                            let borrow = borrow_ident(&name.to_string());
+                            quote_spanned!(span=> &#mutability *#borrow)
+                        }
+                    }
+                })
+                .collect()
+        }
+        RoleMethodBorrows::Context => {
+            let caller = match &caller {
+                Caller::Context(caller) | Caller::Alias(_, caller) => *caller,
+                Caller::Player => unreachable!("role methods borrowing fields only call such methods"),
+            };
+            let receiver = match (role.receiver(method), caller) {
+                (None, _) => None,
+                (Some(SelfType::Value), _) => Some(quote_spanned!(span=> self)),
+                (Some(SelfType::Reference), Some(SelfType::Value)) => Some(quote_spanned!(span=> &self)),
+                (Some(SelfType::MutableReference), Some(SelfType::Value)) => Some(quote_spanned!(span=> &mut self)),
+                (Some(SelfType::Reference), _) => Some(quote_spanned!(span=> &*self)),
+                (Some(SelfType::MutableReference), _) => Some(quote_spanned!(span=> &mut *self)),
+            };
+            receiver.into_iter().collect::<Vec<_>>()
+        }
+    };
+    // Earlier versions dropped the turbofish, keep accepting one on non-generic methods
+    let method_generics = role.methods.iter().find(|m| m.get_name() == method).map(|m| m.get_generics());
+    let turbofish = turbofish.filter(|_| method_generics.is_some_and(|g| !g.get_params().is_empty()));
+    let args = borrows.into_iter().chain(args.iter().map(|arg| arg.to_token_stream()));
+    let call: Expr = syn::parse_quote_spanned!(span=>
+        <Self as #role_trait #ty_generics>::#method #turbofish (#(#args),*)
+    );
+    match (&caller, role.borrows(method)) {
+        // The role method borrows the whole context rather than the alias, which stays in use
+        (Caller::Alias(player, _), RoleMethodBorrows::Context) => syn::parse_quote_spanned!(span=> {
+            let _ = &#player;
+            #call
+        }),
+        _ => call,
+    }
+}
+
+impl Role {
+    pub fn compile(
+        &self,
+        roles: &HashMap<String, Role>,
+        context: &syn::ItemStruct,
+        async_strategy: &AsyncStrategy,
+    ) -> CompiledRole {
+        let context_generics = &context.generics;
+
+        // Create a visitor to rewrite self to self.{role_name}
+        struct SelfRewriter<'a> {
+            role: &'a Role,
+            context_generics: &'a syn::Generics,
+            receiver: Option<SelfType>,
+        }
+
+        impl VisitMut for SelfRewriter<'_> {
+            fn visit_expr_mut(&mut self, expr: &mut Expr) {
+                // `self.context` gives the role method access to the context itself,
+                // i.e. its data fields, its interactions and the other roles
+                if let Expr::Field(field_expr) = expr {
+                    if let (Expr::Path(base_path), Member::Named(member)) =
+                        (&*field_expr.base, &field_expr.member)
+                    {
+                        if base_path.path.is_ident("self") && member == "context" {
+                            *expr = syn::parse_quote!(self);
+                            return;
+                        }
+                    }
+                }
+                if let Expr::Path(expr_path) = expr {
+                    if expr_path.path.is_ident("self") {
+                        let role_name = to_role_name(&self.role.name.to_string());
+                        let role_ident = syn::Ident::new(&role_name, self.role.name.span());
+                        *expr = syn::parse_quote!(self.#role_ident);
+                        return;
+                    }
+                }
+                if let Expr::MethodCall(method_call) = expr {
+                    if let Expr::Path(base_path) = &*method_call.receiver {
+                        if base_path.path.is_ident("self") && self.role.has_method(&method_call.method) {
+                            let mut args = method_call.args.clone();
+                            for arg in args.iter_mut() {
+                                self.visit_expr_mut(arg);
+                            }
+                            *expr = role_method_call(
+                                self.role,
+                                &method_call.method,
+                                method_call.turbofish.as_ref(),
+                                args,
+                                self.context_generics,
+                                Caller::Context(self.receiver),
+                            );
+                            return;
+                        }
+                    }
+                }
+                syn::visit_mut::visit_expr_mut(self, expr);
+            }
+
+            fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
+                rewrite_macro(self, mac);
+            }
+        }
+
+        // Compile the contract trait, if the role has one
+        let contract = self.contract.as_ref().map(|contract| {
+            let mut contract = contract.compile();
+            contract.trait_item.vis = syn::Visibility::Public(syn::token::Pub::default());
+            contract
+        });
+
+        // Rewrites a role method that only borrows its player and data fields, where
+        // `self` is the borrowed player and `self.context.<field>` a borrowed field
+        struct BorrowRewriter<'a> {
+            role: &'a Role,
+            context_generics: &'a syn::Generics,
+            player: syn::Ident,
+            uses_player: bool,
+        }
+
+        impl VisitMut for BorrowRewriter<'_> {
+            fn visit_expr_mut(&mut self, expr: &mut Expr) {
+                if let Expr::Field(field_expr) = expr {
+                    if let (Expr::Field(context), Member::Named(field)) =
+                        (&*field_expr.base, &field_expr.member)
+                    {
+                        if let (Expr::Path(base_path), Member::Named(member)) = (&*context.base, &context.member) {
+                            if base_path.path.is_ident("self") && member == "context" {
+                                let borrow = borrow_ident(&field.to_string());
+                                *expr = syn::parse_quote!((*#borrow));
+                                return;
+                            }
+                        }
+                    }
+                }
+                if let Expr::MethodCall(method_call) = expr {
+                    if let Expr::Path(base_path) = &*method_call.receiver {
+                        if base_path.path.is_ident("self") && self.role.has_method(&method_call.method) {
+                            let mut args = method_call.args.clone();
+                            for arg in args.iter_mut() {
+                                self.visit_expr_mut(arg);
+                            }
+                            self.uses_player = true;
+                            *expr = role_method_call(
+                                self.role,
+                                &method_call.method,
+                                method_call.turbofish.as_ref(),
+                                args,
+                                self.context_generics,
+                                Caller::Player,
+                            );
+                            return;
+                        }
+                    }
+                }
+                if let Expr::Path(expr_path) = expr {
+                    if expr_path.path.is_ident("self") {
+                        let player = &self.player;
+                        self.uses_player = true;
+                        *expr = syn::parse_quote!(#player);
+                        return;
+                    }
+                }
+                syn::visit_mut::visit_expr_mut(self, expr);
+            }
+
+            fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
+                rewrite_macro(self, mac);
+            }
+        }
+
+        let role_field = to_role_name(&self.name.to_string());
+        let player_ty = role_generic_name(&syn::Ident::new(&role_field, self.name.span()));
+
+        // Rewrite methods to access self.{role_name}
+        let functions: Vec<FunctionDescription> = self
+            .methods
+            .iter()
+            .filter_map(|func| match func {
+                FunctionDescription::Implementation {
+                    name,
+                    params,
+                    generics,
+                    output,
+                    body,
+                    asyncness,
+                    attrs,
+                } => {
+                    let mut new_body = body.clone();
+                    let new_params = match self.borrows(name) {
+                        RoleMethodBorrows::Fields { player, fields } => {
+                            let mut rewriter = BorrowRewriter {
+                                role: self,
+                                context_generics,
+                                player: borrow_ident(&role_field),
+                                uses_player: false,
+                            };
+                            rewriter.visit_block_mut(&mut new_body);
+
+                            // The player and the fields replace `self`
+                            let player_name = if rewriter.uses_player {
+                                rewriter.player
+                            } else {
+                                borrow_ident("_")
+                            };
+                            let player_param = ParameterInfo::Typed {
+                                name: player_name,
+                                ty: match player {
+                                    SelfType::MutableReference => syn::parse_quote!(&mut #player_ty),
+                                    _ => syn::parse_quote!(&#player_ty),
+                                },
+                            };
+                            let field_params = fields.iter().map(|field| {
+                                let ty = &field.ty;
+                                let mutability = field.mutable.then(<syn::Token![mut]>::default);
+                                ParameterInfo::Typed {
+                                    name: borrow_ident(&field.name.to_string()),
+                                    ty: syn::parse_quote!(&#mutability #ty),
+                                }
+                            });
+                            std::iter::once(player_param)
+                                .chain(field_params)
+                                .chain(params.iter().skip(1).cloned())
+                                .collect()
+                        }
+                        RoleMethodBorrows::Context => {
+                            let receiver = params.first().filter(|p| p.is_self()).map(|p| p.get_self_type());
+                            let mut rewriter = SelfRewriter {
+                                role: self,
+                                context_generics,
+                                receiver,
+                            };
+                            rewriter.visit_block_mut(&mut new_body);
+                            // Role methods called through `self.context.<role>`
+                            rewrite_role_access(roles, context_generics, receiver, &mut new_body);
+                            params.clone()
+                        }
+                    };
+
+                    Some(FunctionDescription::new_implementation(
+                        name.clone(),
+                        new_params,
+                        generics.clone(),
+                        output.clone(),
+                        new_body,
+                        *asyncness,
+                        attrs.clone(),
+                    ))
+                }
+                FunctionDescription::Declaration { .. } => None,
+            })
+            .collect();
+
+        // The role methods are declared by a private trait of the same name as the role,
+        // which is implemented by the context
+        // Async role methods are declared like the async interactions
+        let is_async = functions.iter().any(|f| f.get_asyncness().is_some());
+        let send = is_async && matches!(async_strategy, AsyncStrategy::Send);
+        let async_trait = match async_strategy {
+            AsyncStrategy::AsyncTrait(attr) if is_async => Some((**attr).clone()),
+            _ => None,
+        };
+        let role_name = &self.name;
+        let role_methods = functions.iter().filter_map(|f| trait_method(f, send));
+        let mut role_trait: syn::ItemTrait = syn::parse_quote! {
+            #async_trait
+            trait #role_name {
+                #(#role_methods)*
+            }
+        };
+        role_trait.generics = context_generics.clone();
+        let (_, ty_generics, _) = context_generics.split_for_impl();
+
+        let context_name = &context.ident;
+        let impl_block = ImplBlockInfo {
+            attrs: async_trait.into_iter().collect(),
+            self_ty: syn::parse_quote!(#context_name #ty_generics),
+            generics: GenericsInfo::from_syn_generics(context_generics),
+            for_lifetimes: None,
+            implemented_traits: vec![syn::parse_quote!(#role_name #ty_generics)],
+            functions,
+        };
+
+        CompiledRole {
+            role_trait,
+            impl_block: impl_block.compile(),
+            contract,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use proc_macro2::Span;
+use quote::quote;
+use syn::visit_mut::VisitMut;
+use syn::Expr;
+
+use crate::analysis::players::{self, Players, VisitPlayers};
+use crate::analysis::{ContextInfo, FunctionDescription, Role};
+
+use super::macro_args::{is_expression_macro, rewrite_macro};
+
+/// Instruments the context for `#[five::context(trace)]`. Interactions run in a span named
+/// `<context>::<interaction>`, role methods in one named `<context>::<role>::<method>`, both
+/// with the context and role as fields. Calls of contract methods on the players are
+/// recorded as `debug` events within those spans.
+pub fn trace_context(module_name: &syn::Ident, context: &ContextInfo) -> ContextInfo {
+    let module_name = module_name.to_string();
+    let five = &context.crate_path;
+    let mut traced = context.clone();
+
+    // Role fields with the role played through them
+    let role_fields: HashMap<String, &Role> = context
+        .role_fields()
+        .into_iter()
+        .filter_map(|role_field| Some((role_field.field.to_string(), role_field.role?)))
+        .collect();
+
+    for block in &mut traced.impl_blocks {
+        for function in &mut block.functions {
+            if let FunctionDescription::Implementation { name, body, asyncness, .. } = function {
+                let span_name = format!("{}::{}", module_name, name);
+                let mut events = ContractCalls::new(five, &role_fields, None);
+                events.visit_block_mut(body);
+                *body = in_span(five, quote!(#span_name, context = #module_name), body, asyncness.is_some());
+            }
+        }
+    }
+
+    for role in &mut traced.roles {
+        let role_name = role.name.to_string();
+        let own_field = role_fields
+            .iter()
+            .find(|(_, player_role)| player_role.name == role.name)
+            .map(|(field, _)| field.clone());
+        for function in &mut role.methods {
+            if let FunctionDescription::Implementation { name, body, asyncness, .. } = function {
+                let span_name = format!("{}::{}::{}", module_name, role_name, name);
+                let mut events = ContractCalls::new(five, &role_fields, own_field.clone());
+                events.visit_block_mut(body);
+                *body = in_span(
+                    five,
+                    quote!(#span_name, context = #module_name, role = #role_name),
+                    body,
+                    asyncness.is_some(),
+                );
+            }
+        }
+    }
+    traced
+}
+
+/// `body` run in the span created from `args`, entered for sync bodies and instrumenting
+/// the future for async ones
+fn in_span(five: &syn::Path, args: proc_macro2::TokenStream, body: &syn::Block, is_async: bool) -> syn::Block {
+    let span = syn::Ident::new("span", Span::mixed_site());
+    let stmts = &body.stmts;
+    if is_async {
+        syn::parse_quote!({
+            let #span = #five::tracing::info_span!(#args);
+            #five::tracing::Instrument::instrument(async move { #(#stmts)* }, #span).await
+        })
+    } else {
+        syn::parse_quote!({
+            let #span = #five::tracing::info_span!(#args).entered();
+            #(#stmts)*
+        })
+    }
+}
+
+/// Records an event before every call of a contract method: a method the role doesn't
+/// implement, called on a player, `player.method(..)`, or through the role or contract,
+/// `Contract::method(player, ..)`
+struct ContractCalls<'a> {
+    five: &'a syn::Path,
+    role_fields: &'a HashMap<String, &'a Role>,
+    players: Players,
+}
+
+impl<'a> ContractCalls<'a> {
+    /// The calls in an interaction or, with `own_field`, in a method of the role played
+    /// through `own_field`
+    fn new(five: &'a syn::Path, role_fields: &'a HashMap<String, &'a Role>, own_field: Option<String>) -> Self {
+        let players = Players::new(role_fields.keys().cloned(), own_field);
+        ContractCalls { five, role_fields, players }
+    }
+
+    /// The role played by `player` when `method` is a method of its contract
+    fn contract_call(&self, player: &Expr, method: &syn::Ident) -> Option<&'a Role> {
+        let player = self.players.player(player)?;
+        let role = *self.role_fields.get(player.field())?;
+        (!role.has_method(method)).then_some(role)
+    }
+}
+
+impl VisitPlayers for ContractCalls<'_> {
+    fn players(&mut self) -> &mut Players {
+        &mut self.players
+    }
+}
+
+impl VisitMut for ContractCalls<'_> {
+    fn visit_expr_mut(&mut self, expr: &mut Expr) {
+        players::visit_expr_mut(self, expr);
+        let call = match &*expr {
+            Expr::MethodCall(call) => self.contract_call(&call.receiver, &call.method).map(|role| (role, &call.method)),
+            Expr::Call(call) => match &*call.func {
+                Expr::Path(path) => match path.path.segments.iter().collect::<Vec<_>>().as_slice() {
+                    [owner, method] => call
+                        .args
+                        .first()
+                        .and_then(|player| self.contract_call(player, &method.ident))
+                        .filter(|role| owner.ident == role.name || role.has_bound(&owner.ident))
+                        .map(|role| (role, &method.ident)),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+        let Some((role, method)) = call else {
+            return;
+        };
+        let contract = role.contract_of(method);
+        let method = method.to_string();
+        let role = role.name.to_string();
+        let five = self.five;
+        *expr = syn::parse_quote!({
+            #five::tracing::debug!(contract = #contract, method = #method, role = #role, "contract call");
+            #expr
+        });
+    }
+
+    fn visit_block_mut(&mut self, block: &mut syn::Block) {
+        players::visit_block_mut(self, block);
+    }
+
+    fn visit_local_mut(&mut self, local: &mut syn::Local) {
+        players::visit_local_mut(self, local);
+    }
+
+    fn visit_macro_mut(&mut self, mac: &mut syn::Macro) {
+        if is_expression_macro(mac) {
+            rewrite_macro(self, mac);
+        }
+    }
+}
@@ -1,5 +1,3 @@
-use std::str::FromStr;
-
 use quote::ToTokens;
 
 use super::{Compiled, Compiler};
@@ -12,7 +10,6 @@ pub struct CompiledTraitInfo {
 
 impl ToTokens for CompiledTraitInfo {
     fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-        tokens.extend(proc_macro2::TokenStream::from_str("\n").unwrap());
         self.trait_item.to_tokens(tokens);
     }
 }
@@ -0,0 +1,202 @@
+//! The analysis and code generation behind `#[five::context]`.
+//!
+//! [`analysis`] turns a context module into a model of its roles, contracts and
+//! interactions, [`compile`] turns that model into the generated items. [`expand`] runs
+//! both, so build scripts, linters and test harnesses can expand contexts the same way the
+//! macro does. [`sources`] finds the context modules of a crate and, with the `manifest`
+//! feature, `manifest` describes them as JSON.
+pub mod analysis;
+pub mod compile;
+#[cfg(feature = "manifest")]
+pub mod manifest;
+pub mod sources;
+
+use proc_macro2::TokenStream;
+use syn::ItemMod;
+
+use crate::analysis::errors::{take_errors, MacroError};
+use crate::analysis::{analyze_module, ContextAttributes, ModuleInfo};
+use crate::compile::module::CompiledModule;
+use crate::compile::{Compiled, Compiler};
+
+/// Expands a context module carrying its `#[five::context(...)]` attribute, e.g. as read
+/// from a source file.
+///
+/// The attribute is recognised by its last path segment, `context`, and is removed before
+/// expanding. A module without it is expanded with the default attributes.
+pub fn expand(item: TokenStream) -> Result<TokenStream, Vec<MacroError>> {
+    let (attr, module) = split_context_attribute(item)?;
+    expand_module(attr, module)
+}
+
+/// Expands a context module given the arguments of its attribute separately, as an
+/// attribute macro receives them
+pub fn expand_context(attr: TokenStream, item: TokenStream) -> Result<TokenStream, Vec<MacroError>> {
+    let module: ItemMod = syn::parse2(item).map_err(|err| vec![err.into()])?;
+    expand_module(attr, module)
+}
+
+/// Expands `#[five::mock]` on a contract trait: the trait followed by its mock
+pub fn expand_mock(attr: TokenStream, item: TokenStream) -> Result<TokenStream, Vec<MacroError>> {
+    expand_contract(attr, item, "mock", compile::mock::compile_mock)
+}
+
+/// Expands `#[five::record]` on a contract trait: the trait followed by its recording and
+/// replaying players
+pub fn expand_record(attr: TokenStream, item: TokenStream) -> Result<TokenStream, Vec<MacroError>> {
+    expand_contract(attr, item, "record", compile::record::compile_record)
+}
+
+/// Expands an attribute generating players for a contract trait
+fn expand_contract(
+    attr: TokenStream,
+    item: TokenStream,
+    name: &str,
+    players: fn(&syn::ItemTrait, &syn::Path) -> TokenStream,
+) -> Result<TokenStream, Vec<MacroError>> {
+    // `crate = path`, the path of the `five` crate in generated code, as for contexts
+    let crate_path = |input: syn::parse::ParseStream| -> syn::Result<syn::Path> {
+        if input.is_empty() {
+            return Ok(syn::parse_quote!(::five));
+        }
+        if !input.peek(syn::Token![crate]) {
+            return Err(input.error(format!("`{}` only takes `crate = path`", name)));
+        }
+        input.parse::<syn::Token![crate]>()?;
+        input.parse::<syn::Token![=]>()?;
+        let path = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error(format!("`{}` only takes `crate = path`", name)));
+        }
+        Ok(path)
+    };
+    let five = syn::parse::Parser::parse2(crate_path, attr).map_err(|err| vec![err.into()])?;
+    let contract: syn::ItemTrait = syn::parse2(item).map_err(|err| vec![err.into()])?;
+
+    take_errors();
+    let players = players(&contract, &five);
+    let errors = take_errors();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(quote::quote! {
+        #contract
+        #players
+    })
+}
+
+/// Analyzes a context module carrying its `#[five::context(...)]` attribute without
+/// compiling it, for tools inspecting the DCI model of a crate
+pub fn analyze(item: TokenStream) -> Result<ModuleInfo, Vec<MacroError>> {
+    let (attr, module) = split_context_attribute(item)?;
+    take_errors();
+    let analyzed = analyze_attributed(attr, &module).map_err(|err| vec![err])?;
+    let errors = take_errors();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(analyzed)
+}
+
+/// Whether `attr` is the `#[five::context]` attribute, however the macro is referred to
+pub fn is_context_attribute(attr: &syn::Attribute) -> bool {
+    attr.path().segments.last().is_some_and(|segment| segment.ident == "context")
+}
+
+/// Parses a module and takes the arguments of its context attribute off it
+fn split_context_attribute(item: TokenStream) -> Result<(TokenStream, ItemMod), Vec<MacroError>> {
+    let mut module: ItemMod = syn::parse2(item).map_err(|err| vec![err.into()])?;
+    let attr = module
+        .attrs
+        .iter()
+        .position(is_context_attribute)
+        .map(|index| module.attrs.remove(index));
+    let attr = match attr.map(|attr| attr.meta) {
+        Some(syn::Meta::List(list)) => list.tokens,
+        _ => TokenStream::new(),
+    };
+    Ok((attr, module))
+}
+
+fn expand_module(attr: TokenStream, module: ItemMod) -> Result<TokenStream, Vec<MacroError>> {
+    // Errors are collected per expansion, drop anything left by an earlier one
+    take_errors();
+
+    let analyzed = analyze_attributed(attr, &module).map_err(|err| vec![err])?;
+    let compiled = compile(analyzed).map_err(|err| vec![err])?;
+    let emitted = compiled.emit();
+
+    // Report the errors found along the way, e.g. role method calls that couldn't be rewritten
+    let errors = take_errors();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    Ok(emitted)
+}
+
+/// Parses the arguments of the attribute, e.g. `provides(...)`, and analyzes the module
+fn analyze_attributed(attr: TokenStream, module: &ItemMod) -> Result<ModuleInfo, MacroError> {
+    let attributes: ContextAttributes = syn::parse2(attr)?;
+    if module.content.is_none() {
+        return Err(MacroError::new(
+            "Module must have a body (missing braces)",
+            module.ident.span(),
+        ));
+    }
+    analyze_checked(module, attributes)
+}
+
+/// Analyzes a module after checking it has exactly one Context struct
+fn analyze_checked(module: &ItemMod, attributes: ContextAttributes) -> Result<ModuleInfo, MacroError> {
+    let context_count = module
+        .content
+        .iter()
+        .flat_map(|(_, items)| items)
+        .filter(|item| matches!(item, syn::Item::Struct(item_struct) if item_struct.ident == "Context"))
+        .count();
+    if context_count == 0 {
+        return Err(MacroError::new(
+            "Missing Context struct - each module must define exactly one Context struct",
+            module.ident.span(),
+        ));
+    } else if context_count > 1 {
+        return Err(MacroError::new(
+            format!("Found {} Context structs - each module must define exactly one Context struct", context_count),
+            module.ident.span(),
+        ));
+    }
+
+    Ok(analyze_module(module, attributes))
+}
+
+/// Compiles an analyzed module after checking it has something to compile
+fn compile(module: ModuleInfo) -> Result<CompiledModule, MacroError> {
+    if module.context.properties.is_empty() {
+        return Err(MacroError::new(
+            "Context struct must have at least one property",
+            module.context.name.span(),
+        ));
+    }
+    if module.context.impl_blocks.is_empty() {
+        return Err(MacroError::new(
+            "Context struct must have at least one impl block",
+            module.context.name.span(),
+        ));
+    }
+
+    let compiled = module.compile();
+
+    // Only generics of the Context can be fixed when providing a contract
+    for provided in &compiled.provides {
+        for (generic, _) in &provided.substitutions {
+            if !compiled.context.base.generics.type_params().any(|tp| tp.ident == *generic) {
+                return Err(MacroError::new(
+                    format!("`{}` is not a generic parameter of the Context struct", generic),
+                    generic.span(),
+                ));
+            }
+        }
+    }
+
+    Ok(compiled)
+}
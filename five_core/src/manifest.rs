@@ -0,0 +1,315 @@
+//! A JSON manifest of the contexts of a crate, for tools cataloguing DCI use cases.
+//!
+//! The manifest is written by `five manifest`, or from a build script into `OUT_DIR`:
+//!
+//! ```ignore
+//! let sources = five_core::sources::find_contexts(Path::new("src"))?;
+//! let manifest = Manifest::from_sources(crate_name(Path::new(".")), &sources)?;
+//! manifest.write(&Path::new(&env::var("OUT_DIR")?).join("five-manifest.json"))?;
+//! ```
+//!
+//! # Schema, version 1
+//!
+//! - `schema_version`: [`SCHEMA_VERSION`], raised whenever a field is removed or changes
+//!   meaning. Fields may be added without raising it.
+//! - `crate`: the package name, `null` if no `Cargo.toml` was found.
+//! - `contexts`: one entry per context, ordered by file and then by position in the file:
+//!   - `name`: the context module, `path`: the module qualified with the module it's
+//!     expanded into, `module`: that module, `file`: the source file.
+//!   - `context_trait`: the trait returned by `bind`.
+//!   - `contracts`: the contracts declared in the context module, with the signatures
+//!     of their methods.
+//!   - `roles`: the role fields, each with its role trait, the traits a player must
+//!     implement and the role methods. Every role method lists under `uses` the player
+//!     methods it calls, directly or through the role methods and interactions it calls.
+//!   - `data`: the fields holding data, with their types.
+//!   - `interactions`: the interactions with their signatures.
+//!   - `plays`: the contracts of other contexts this context provides, so it can play a
+//!     role in them, with the context and role using the contract when it's in the crate.
+//!
+//! Types and signatures are written as in the source.
+use std::io;
+use std::path::Path;
+
+use crate::analysis::{
+    analyze_role_method_calls, compact, context_trait_name, player_method_calls, Call, Callee, FunctionDescription,
+    ModuleInfo, RoleField, TypeDescription,
+};
+use serde::Serialize;
+
+use crate::sources::ContextSource;
+
+/// The version of the manifest schema
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+pub struct Manifest {
+    pub schema_version: u32,
+    #[serde(rename = "crate")]
+    pub crate_name: Option<String>,
+    pub contexts: Vec<ContextManifest>,
+}
+
+#[derive(Serialize)]
+pub struct ContextManifest {
+    pub name: String,
+    pub path: String,
+    pub module: String,
+    pub file: String,
+    pub context_trait: String,
+    pub contracts: Vec<ContractManifest>,
+    pub roles: Vec<RoleManifest>,
+    pub data: Vec<DataManifest>,
+    pub interactions: Vec<MethodManifest>,
+    pub plays: Vec<PlaysManifest>,
+}
+
+#[derive(Serialize)]
+pub struct ContractManifest {
+    pub name: String,
+    pub methods: Vec<MethodManifest>,
+}
+
+#[derive(Serialize)]
+pub struct RoleManifest {
+    pub name: String,
+    pub field: String,
+    pub contracts: Vec<String>,
+    pub methods: Vec<RoleMethodManifest>,
+}
+
+#[derive(Serialize)]
+pub struct RoleMethodManifest {
+    pub name: String,
+    pub signature: String,
+    pub uses: Vec<PlayerMethodManifest>,
+}
+
+/// A method of the player bound to `field`
+#[derive(Serialize)]
+pub struct PlayerMethodManifest {
+    pub field: String,
+    pub contract: String,
+    pub method: String,
+}
+
+#[derive(Serialize)]
+pub struct MethodManifest {
+    pub name: String,
+    pub signature: String,
+}
+
+#[derive(Serialize)]
+pub struct DataManifest {
+    pub name: String,
+    pub ty: String,
+}
+
+#[derive(Serialize)]
+pub struct PlaysManifest {
+    /// The provided contract, as written in the context attribute
+    pub contract: String,
+    /// The `path` of the context using the contract, if it's in the crate
+    pub context: Option<String>,
+    /// The role of that context requiring the contract
+    pub role: Option<String>,
+}
+
+impl Manifest {
+    /// The manifest of the contexts in `sources`, or the errors of those that don't analyze
+    pub fn from_sources(crate_name: Option<String>, sources: &[ContextSource]) -> Result<Self, Vec<String>> {
+        let mut errors = vec![];
+        let mut contexts = vec![];
+        for source in sources {
+            match crate::analyze(source.tokens()) {
+                Ok(module) => contexts.push(ContextManifest::new(source, &module)),
+                Err(errs) => errors.extend(errs.iter().map(|err| {
+                    format!("{}: context `{}`: {}", source.file.display(), source.module.ident, err)
+                })),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        resolve_plays(&mut contexts);
+        Ok(Manifest {
+            schema_version: SCHEMA_VERSION,
+            crate_name,
+            contexts,
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn write(&self, file: &Path) -> io::Result<()> {
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(file, self.to_json())
+    }
+}
+
+impl ContextManifest {
+    pub fn new(source: &ContextSource, module: &ModuleInfo) -> Self {
+        let context = &module.context;
+        let name = module.module_name.to_string();
+
+        let contracts = context
+            .roles
+            .iter()
+            .filter(|role| role.external_contract.is_none())
+            .filter_map(|role| role.contract.as_ref())
+            .chain(module.others.iter().filter_map(|other| match other {
+                TypeDescription::RoleContract(contract) => Some(contract),
+                _ => None,
+            }))
+            .map(|contract| ContractManifest {
+                name: contract.name.to_string(),
+                methods: methods(&contract.functions),
+            })
+            .collect();
+
+        let role_method_calls = analyze_role_method_calls(context);
+        let roles = context
+            .role_fields()
+            .into_iter()
+            .map(|RoleField { field, role_name, role, contracts }| {
+                let field = field.to_string();
+                let methods = role
+                    .map(|role| {
+                        role.methods
+                            .iter()
+                            .map(|function| {
+                                let calls = role_method_calls
+                                    .iter()
+                                    .find(|calls| calls.field == field && function.get_name() == &calls.method);
+                                RoleMethodManifest {
+                                    name: function.get_name().to_string(),
+                                    signature: function.signature(),
+                                    uses: calls.map(|calls| player_methods(&calls.calls)).unwrap_or_default(),
+                                }
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                RoleManifest {
+                    name: role_name.to_string(),
+                    field,
+                    contracts,
+                    methods,
+                }
+            })
+            .collect();
+
+        let data = context
+            .properties
+            .iter()
+            .filter(|prop| !prop.is_role())
+            .map(|prop| DataManifest {
+                name: prop.get_name().to_string(),
+                ty: compact(&prop.get_ty()),
+            })
+            .collect();
+
+        let interactions: Vec<FunctionDescription> =
+            context.impl_blocks.iter().flat_map(|block| block.functions.clone()).collect();
+
+        ContextManifest {
+            path: format!("{}::{}", source.module_path, name),
+            name,
+            module: source.module_path.clone(),
+            file: source.file.display().to_string(),
+            context_trait: context_trait_name(&module.module_name).to_string(),
+            contracts,
+            roles,
+            data,
+            interactions: methods(&interactions),
+            plays: module
+                .attributes
+                .provides
+                .iter()
+                .map(|provided| PlaysManifest {
+                    contract: compact(&provided.contract),
+                    context: None,
+                    role: None,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The package name in the nearest `Cargo.toml` above `path`. A name inherited with
+/// `name.workspace = true` is taken from `[workspace.package]` of the workspace above it.
+pub fn crate_name(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let mut manifests = path
+        .ancestors()
+        .map(|dir| dir.join("Cargo.toml"))
+        .filter(|file| file.is_file())
+        .filter_map(|file| std::fs::read_to_string(file).ok()?.parse::<toml::Table>().ok());
+    let manifest = manifests.next()?;
+    match manifest.get("package")?.get("name")? {
+        toml::Value::String(name) => Some(name.clone()),
+        toml::Value::Table(name) if name.get("workspace").and_then(toml::Value::as_bool) == Some(true) => {
+            // The workspace is the package's own manifest or the nearest one above it
+            std::iter::once(manifest.clone())
+                .chain(manifests)
+                .find_map(|manifest| manifest.get("workspace")?.get("package")?.get("name")?.as_str().map(str::to_string))
+        }
+        _ => None,
+    }
+}
+
+fn methods(functions: &[FunctionDescription]) -> Vec<MethodManifest> {
+    functions
+        .iter()
+        .map(|function| MethodManifest {
+            name: function.get_name().to_string(),
+            signature: function.signature(),
+        })
+        .collect()
+}
+
+fn player_methods(calls: &[Call]) -> Vec<PlayerMethodManifest> {
+    player_method_calls(calls)
+        .into_iter()
+        .filter_map(|callee| match callee {
+            Callee::PlayerMethod { field, contract, method, .. } => Some(PlayerMethodManifest { field, contract, method }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Finds the context and role each provided contract is required by. A contract given by
+/// its `crate::` path must be declared in the module the context is expanded into,
+/// otherwise the contract name alone decides.
+fn resolve_plays(contexts: &mut [ContextManifest]) {
+    let mut resolved = vec![];
+    for (index, context) in contexts.iter().enumerate() {
+        for (play_index, play) in context.plays.iter().enumerate() {
+            let (module, contract) = match play.contract.rsplit_once("::") {
+                Some((module, contract)) => (module.starts_with("crate").then_some(module), contract),
+                None => (None, play.contract.as_str()),
+            };
+            let user = contexts
+                .iter()
+                .filter(|other| module.is_none_or(|module| other.module == module))
+                .find_map(|other| {
+                    let role = other.roles.iter().find(|role| {
+                        role.contracts.iter().any(|name| name.rsplit("::").next() == Some(contract))
+                    })?;
+                    Some((other.path.clone(), role.name.clone()))
+                });
+            if let Some(user) = user {
+                resolved.push((index, play_index, user));
+            }
+        }
+    }
+    for (index, play_index, (context, role)) in resolved {
+        let play = &mut contexts[index].plays[play_index];
+        play.context = Some(context);
+        play.role = Some(role);
+    }
+}
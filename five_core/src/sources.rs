@@ -0,0 +1,124 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use quote::ToTokens;
+
+use crate::is_context_attribute;
+
+/// A module carrying the context attribute, as found in the sources of a crate
+pub struct ContextSource {
+    /// The file the module is written in
+    pub file: PathBuf,
+    /// The path of the module the context is expanded into, e.g. `crate::account`.
+    /// The public items of the context module are re-exported into it
+    pub module_path: String,
+    pub module: syn::ItemMod,
+}
+
+impl ContextSource {
+    /// The tokens of the module, including its context attribute
+    pub fn tokens(&self) -> proc_macro2::TokenStream {
+        self.module.to_token_stream()
+    }
+}
+
+/// Finds the context modules in `path`, a crate directory, a source directory or a
+/// single file. Files that fail to parse are reported on stderr and skipped.
+pub fn find_contexts(path: &Path) -> io::Result<Vec<ContextSource>> {
+    let mut files = vec![];
+    let root = if path.is_dir() {
+        let root = if path.join("src").is_dir() { path.join("src") } else { path.to_path_buf() };
+        collect_files(&root, &mut files)?;
+        root
+    } else {
+        files.push(path.to_path_buf());
+        // Module paths of a single file are relative to the `src` directory it's in
+        path.ancestors()
+            .find(|ancestor| ancestor.ends_with("src"))
+            .or(path.parent())
+            .unwrap_or(path)
+            .to_path_buf()
+    };
+    files.sort();
+
+    let mut contexts = vec![];
+    for file in files {
+        let source = fs::read_to_string(&file)?;
+        match syn::parse_file(&source) {
+            Ok(parsed) => {
+                let module_path = file_module_path(&root, &file);
+                collect_contexts(&file, &module_path, &parsed.items, &mut contexts);
+            }
+            Err(err) => eprintln!("Skipping {}: {}", file.display(), err),
+        }
+    }
+    Ok(contexts)
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// The module path of a source file relative to the source root, assuming the usual
+/// `src/a/b.rs` or `src/a/b/mod.rs` layout
+fn file_module_path(root: &Path, file: &Path) -> String {
+    let relative = file.strip_prefix(root).unwrap_or(file).with_extension("");
+    let mut segments: Vec<String> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    if matches!(segments.last().map(String::as_str), Some("mod" | "lib" | "main")) {
+        segments.pop();
+    }
+    std::iter::once("crate".to_string()).chain(segments).collect::<Vec<_>>().join("::")
+}
+
+fn collect_contexts(file: &Path, module_path: &str, items: &[syn::Item], contexts: &mut Vec<ContextSource>) {
+    for item in items {
+        let syn::Item::Mod(module) = item else {
+            continue;
+        };
+        if module.attrs.iter().any(is_context_attribute) {
+            contexts.push(ContextSource {
+                file: file.to_path_buf(),
+                module_path: module_path.to_string(),
+                module: module.clone(),
+            });
+        } else if let Some((_, items)) = &module.content {
+            collect_contexts(file, &format!("{}::{}", module_path, module.ident), items, contexts);
+        }
+    }
+}
+
+/// The context named `name`, either by its module name or qualified with the module it's
+/// expanded into, e.g. `crate::account::account`. The error explains a missing or
+/// ambiguous name.
+pub fn select_context<'a>(sources: &'a [ContextSource], name: &str) -> Result<&'a ContextSource, String> {
+    let matches: Vec<&ContextSource> = sources
+        .iter()
+        .filter(|source| {
+            let ident = source.module.ident.to_string();
+            name == ident || name == format!("{}::{}", source.module_path, ident)
+        })
+        .collect();
+    match matches.as_slice() {
+        [source] => Ok(source),
+        [] => Err(format!("No context named `{}`", name)),
+        _ => {
+            let mut message = format!("`{}` is ambiguous, qualify it with its module:", name);
+            for source in matches {
+                message.push_str(&format!("\n  {}::{}", source.module_path, source.module.ident));
+            }
+            Err(message)
+        }
+    }
+}
@@ -13,8 +13,15 @@ pub mod account {
                 LedgerEntry::Withdrawal(msg, _) => msg.to_string(),
             }
         }
+        fn describe(&self) -> String {
+            match self {
+                LedgerEntry::Deposit(msg, amount) => format!("{} +{}", msg, amount),
+                LedgerEntry::Withdrawal(msg, amount) => format!("{} -{}", msg, amount),
+            }
+        }
     }
 
+    #[cfg_attr(test, five::mock)]
     pub trait LedgerContract {
         fn push(&mut self, entry: LedgerEntry);
         fn as_vec(&self) -> Vec<LedgerEntry>;
@@ -26,7 +33,10 @@ pub mod account {
             self.log(entry.message());
         }
         fn log(&self, msg: String) {
-            println!("{}", msg);
+            println!("{}: {} (balance {})", self.context.account_no, msg, self.context.balance());
+        }
+        fn describe(&self, entry: &LedgerEntry) -> String {
+            format!("#{} {}", self.context.account_no, entry.message())
         }
     }
 
@@ -41,6 +51,26 @@ pub mod account {
         fn withdraw(&mut self, message: String, amount: i32) {
             self.ledger.add(LedgerEntry::Withdrawal(message, amount))
         }
+        fn deposit_all(&mut self, deposits: Vec<(String, i32)>) {
+            deposits
+                .into_iter()
+                .for_each(|(message, amount)| self.ledger.add(LedgerEntry::Deposit(message, amount)));
+        }
+        fn transfer_fee(&mut self, amount: i32) {
+            let ledger = &mut self.ledger;
+            ledger.add(LedgerEntry::Withdrawal(String::from("Fee"), amount));
+            LedgerRole::log(&self.ledger, format!("Charged fee of {}", amount));
+        }
+
+        // The closure parameter shadows the alias only inside the closure, so there
+        // `ledger.describe()` is the entry's own method
+        fn describe_entries(&self) -> Vec<String> {
+            let ledger = &self.ledger;
+            let entries = ledger.as_vec();
+            let mut lines: Vec<String> = entries.iter().map(|ledger| ledger.describe()).collect();
+            lines.extend(entries.last().map(|entry| ledger.describe(entry)));
+            lines
+        }
 
         fn balance(&self) -> i32 {
             self.ledger
@@ -52,6 +82,36 @@ pub mod account {
                 })
                 .sum()
         }
+        fn statement(&self) -> Vec<String> {
+            let lines: Vec<String> = self.ledger
+                .as_vec()
+                .iter()
+                .map(|entry| self.ledger.describe(entry))
+                .collect();
+            if let Some(entry) = self.ledger.as_vec().last() {
+                assert_eq!(lines.last(), Some(&self.ledger.describe(entry)));
+            }
+            lines
+        }
+        // Role methods are dispatched through the role trait, so an interaction may use
+        // any name, including the `<role>_<method>` names earlier versions generated
+        fn ledger_describe(&self, index: usize) -> Option<String> {
+            self.ledger.as_vec().get(index).map(|entry| self.ledger.describe(entry))
+        }
+        // Role calls inside async blocks are rewritten like those inside closures
+        fn describe_later(&self, index: usize) -> impl core::future::Future<Output = Option<String>> + '_ {
+            async move {
+                let entry = self.ledger.as_vec().get(index).cloned()?;
+                Some(self.ledger.describe(&entry))
+            }
+        }
+        // Role calls inside `matches!` are rewritten, in the matched value and in the guard
+        fn charged_fee(&self) -> bool {
+            let entries = self.ledger.as_vec();
+            let in_value = matches!(entries.last().map(|e| self.ledger.describe(e)), Some(line) if line.ends_with("Fee"));
+            let in_guard = matches!(entries.last(), Some(entry) if self.ledger.describe(entry).ends_with("Fee"));
+            in_value && in_guard
+        }
         fn get_account_no(&self) -> i64 {
             self.account_no
         }
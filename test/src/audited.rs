@@ -0,0 +1,43 @@
+#[five::context]
+pub mod audited {
+    use crate::shared::LogContract;
+
+    // The contract is named explicitly
+    #[role(contract = crate::shared::CounterContract)]
+    trait CounterRole {
+        fn describe(&self) -> String {
+            let count = self.count();
+            format!("{} items", count)
+        }
+    }
+
+    // The contract is found by name among the imports
+    trait LogRole {
+        fn record(&mut self, line: &str) {
+            self.write(line.to_string());
+        }
+    }
+
+    struct Context {
+        counter: CounterRole,
+        log: LogRole,
+        audit: crate::shared::AuditRole,
+    }
+
+    impl Context {
+        fn run(&mut self) -> String {
+            let description = self.counter.describe();
+            self.log.record(&description);
+            self.audit.audit("run");
+            description
+        }
+
+        fn audit_trail(&self) -> Vec<String> {
+            self.audit.lines()
+        }
+
+        fn log_lines(&self) -> Vec<String> {
+            self.log.lines()
+        }
+    }
+}
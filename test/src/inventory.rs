@@ -0,0 +1,34 @@
+// Interactions return `Send` futures, so the context can be moved into spawned tasks, and
+// they run in `tracing` spans recording the calls of the players
+#[five::context(send, trace)]
+pub mod inventory {
+    pub trait StockContract {
+        fn count(&self, item: &str) -> u32;
+    }
+
+    trait StockRole: StockContract {
+        fn available(&self, item: &str) -> bool {
+            self.count(item) > 0
+        }
+        async fn reserve(&self, item: &str) -> bool {
+            tokio::task::yield_now().await;
+            self.available(item)
+        }
+    }
+
+    struct Context {
+        stock: StockRole,
+        item: String,
+    }
+
+    impl Context {
+        async fn check(&self) -> bool {
+            self.stock.reserve(&self.item).await
+        }
+        // The stock of the item and of `other`, counted through an alias and the contract
+        fn total(&self, other: &str) -> u32 {
+            let stock = &self.stock;
+            stock.count(&self.item) + StockContract::count(&self.stock, other)
+        }
+    }
+}
@@ -0,0 +1,37 @@
+// The generated code refers to `five` through the re-export given with `crate`
+#[five::context(crate = crate::dci)]
+pub mod labelled {
+    #[cfg_attr(test, crate::dci::mock(crate = crate::dci))]
+    #[crate::dci::record(crate = crate::dci)]
+    pub trait CounterContract {
+        fn count(&self) -> u32;
+    }
+
+    trait CounterRole: CounterContract {
+        fn total(&self) -> u32 {
+            self.count() * self.context.multiplier
+        }
+    }
+
+    struct Context<TLabel: Clone> {
+        counter: CounterRole,
+        label: TLabel,
+        multiplier: u32,
+    }
+
+    impl<TLabel: Clone> Context<TLabel> {
+        pub fn label(&self) -> TLabel {
+            self.label.clone()
+        }
+    }
+
+    // Only contexts with a printable label can describe themselves
+    impl<TLabel> Context<TLabel>
+    where
+        TLabel: Clone + std::fmt::Display,
+    {
+        pub fn describe(&self) -> String {
+            format!("{}: {}", self.label, self.counter.total())
+        }
+    }
+}
@@ -1,9 +1,23 @@
 mod account;
+mod audited;
+mod inventory;
+mod labelled;
+mod metered;
+mod notification;
+mod shared;
 mod storage;
+mod supertrait_test;
+mod transfer;
+mod tree;
 mod user_profile_context;
+mod window;
+#[allow(dead_code, clippy::module_inception)]
 mod data;
+// `five` under another name, as `labelled` uses it
+use five as dci;
 use std::collections::HashMap;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
 use account::LedgerContract;
 use data::data::UserProfile;
 use lazy_static::lazy_static;
@@ -19,7 +33,23 @@ lazy_static! {
 #[tokio::main]
 async fn main() {
     test_account();
+    test_tree();
+    test_contractless_roles();
+    test_imported_contracts();
+    test_transfer();
+    test_conditional_interactions();
+    test_no_std_contexts();
+    test_expand();
+    test_inventory();
+    test_expand_command();
+    test_diagram();
+    test_descriptor();
+    test_manifest();
     test_storage().await;
+    test_record_replay().await;
+    test_send_futures().await;
+    test_tracing().await;
+    test_interceptor().await;
     test_user_profile_context().await;
 }
 
@@ -71,6 +101,159 @@ async fn test_storage() {
     store.store(key.to_string(), &data).await.unwrap();
     let data = store.retrieve(key.to_string()).await.unwrap();
     println!("Data: {:?}", data);
+
+    // The store role retries a store that fails its first attempts
+    struct FlakyStore(AtomicU32);
+    #[async_trait::async_trait]
+    impl StoreContract for FlakyStore {
+        async fn store(&self, key: String, data: Vec<u8>) -> Result<String, String> {
+            if self.0.fetch_add(1, Ordering::SeqCst) < 2 {
+                return Err("Store unavailable".to_string());
+            }
+            InMemoryStore.store(key, data).await
+        }
+        async fn retrieve(&self, key: String) -> Result<Vec<u8>, String> {
+            InMemoryStore.retrieve(key).await
+        }
+    }
+    let store = storage::bind::<Data, _, _, _>(Serialiser, Encrypter, FlakyStore(AtomicU32::new(0)));
+    assert_eq!(store.store("SecondKey".to_string(), &data).await, Ok("SecondKey".to_string()));
+    assert_eq!(store.retrieve("SecondKey".to_string()).await.unwrap().value, data.value);
+}
+
+// The mocks are generated for tests only, see `#[cfg_attr(test, five::mock)]` on the contracts
+#[tokio::test]
+async fn test_mocks() {
+    use account::{Account, LedgerEntry, MockLedgerContract};
+    use storage::{MockEncrypterContract, MockStoreContract};
+
+    // The ledger answers `as_vec` with a fixed history and records the entries pushed
+    let mut ledger = MockLedgerContract::new();
+    ledger
+        .expect_as_vec()
+        .returning(Box::new(|| vec![LedgerEntry::Deposit("Opening".to_string(), 40)]));
+    ledger.expect_push().times(1);
+    let mut account = account::bind(ledger, 7);
+    account.deposit("Salary".to_string(), 100);
+    assert_eq!(account.balance(), 40);
+
+    // The calls are recorded with their arguments, the balance logged by `add` included
+    let ledger = account.replace_ledger(MockLedgerContract::new());
+    assert_eq!(ledger.calls(), [r#"push(Deposit("Salary", 100))"#, "as_vec()", "as_vec()"]);
+    ledger.verify();
+
+    // Async contract methods and borrowed results: the store fails twice before the
+    // storage context's retry succeeds
+    let mut store = MockStoreContract::new();
+    let mut attempts = 0;
+    store
+        .expect_store()
+        .returning(Box::new(move |key, _| {
+            attempts += 1;
+            if attempts < 3 { Err("Store unavailable".to_string()) } else { Ok(key) }
+        }))
+        .times(3);
+    store.expect_retrieve().never();
+    let mut encrypter = MockEncrypterContract::new();
+    encrypter.expect_get_key().returning(Box::new(|| b"an example very very secret key."));
+
+    let mut storage = storage::bind::<Data, _, _, _>(Serialiser, encrypter, store);
+    let data = Data { value: "mocked".to_string() };
+    assert_eq!(storage.store("Key".to_string(), &data).await, Ok("Key".to_string()));
+    let store = storage.replace_store(MockStoreContract::new());
+    assert_eq!(store.calls().len(), 3);
+    assert!(store.calls()[2].starts_with(r#"store("Key", ["#));
+
+    // The mocks of a crate using `five` under another name refer to it by `crate`
+    let mut counter = labelled::MockCounterContract::new();
+    counter.expect_count().returning(Box::new(|| 4));
+    use labelled::Labelled;
+    assert_eq!(labelled::bind(counter, "pears", 2).describe(), "pears: 8");
+
+    // Contract methods can't take the names of the methods of the mock
+    let errors = five_core::expand_mock(
+        quote::quote!(),
+        quote::quote! {
+            pub trait TallyContract {
+                fn calls(&self) -> u32;
+                fn count(&self) -> u32;
+                fn expect_count(&self);
+            }
+        },
+    )
+    .expect_err("the contract methods should be reported");
+    let messages: Vec<String> = errors.iter().map(|err| err.to_string()).collect();
+    assert_eq!(
+        messages,
+        [
+            "`calls` is a method of the generated `MockTallyContract`, rename this contract method",
+            "`expect_count` is a method of the generated `MockTallyContract`, rename this contract method",
+        ]
+    );
+}
+
+async fn test_record_replay() {
+    use five::record::{Recorder, Replayer, Transcript};
+    use storage::{RecordingStoreContract, ReplayingStoreContract};
+
+    // Record a run against the real store
+    let recorder = Recorder::new();
+    let store = RecordingStoreContract::new(InMemoryStore, &recorder);
+    let storage = storage::bind::<Data, _, _, _>(Serialiser, Encrypter, store);
+    let data = Data { value: "recorded".to_string() };
+    assert_eq!(storage.store("Recorded".to_string(), &data).await, Ok("Recorded".to_string()));
+    assert_eq!(storage.retrieve("Recorded".to_string()).await.unwrap().value, "recorded");
+
+    let transcript = recorder.transcript();
+    assert_eq!(transcript.calls.len(), 2);
+    assert_eq!(transcript.calls[0].method, "store");
+    assert_eq!(transcript.calls[0].args[0], "Recorded");
+    assert_eq!(transcript.calls[0].result, serde_json::json!({ "Ok": "Recorded" }));
+    let json = transcript.to_json();
+
+    // Replay it without the store. The data is encrypted with a random nonce, so what is
+    // stored differs between runs and only the calls themselves are checked
+    let replayer = Replayer::new(Transcript::from_json(&json).unwrap()).ignoring_args();
+    let storage = storage::bind::<Data, _, _, _>(Serialiser, Encrypter, ReplayingStoreContract::new(&replayer));
+    assert_eq!(storage.store("Recorded".to_string(), &data).await, Ok("Recorded".to_string()));
+    assert!(!replayer.is_finished());
+    assert_eq!(storage.retrieve("Recorded".to_string()).await.unwrap().value, "recorded");
+    assert!(replayer.is_finished());
+
+    // Retrieving takes the key only, so a recorded retrieval replays with its arguments
+    // checked, and a retrieval of another key is caught
+    let recorder = Recorder::new();
+    let store = RecordingStoreContract::new(InMemoryStore, &recorder);
+    let storage = storage::bind::<Data, _, _, _>(Serialiser, Encrypter, store);
+    assert_eq!(storage.retrieve("Recorded".to_string()).await.unwrap().value, "recorded");
+    let transcript = recorder.transcript();
+    assert_eq!(transcript.calls[0].args, [serde_json::json!("Recorded")]);
+
+    let replayer = Replayer::new(transcript.clone());
+    let storage = storage::bind::<Data, _, _, _>(Serialiser, Encrypter, ReplayingStoreContract::new(&replayer));
+    assert_eq!(storage.retrieve("Recorded".to_string()).await.unwrap().value, "recorded");
+    assert!(replayer.is_finished());
+
+    let replayer = Replayer::new(transcript);
+    let storage = storage::bind::<Data, _, _, _>(Serialiser, Encrypter, ReplayingStoreContract::new(&replayer));
+    let other_key = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        futures::executor::block_on(storage.retrieve("Other".to_string()))
+    }));
+    let message = other_key.err().unwrap().downcast::<String>().unwrap();
+    assert_eq!(*message, r#"call 0 of StoreContract::retrieve was recorded with [String("Recorded")], not [String("Other")]"#);
+    assert!(!replayer.is_finished());
+
+    // The players of a crate using `five` under another name refer to it by `crate`
+    struct Basket;
+    impl labelled::CounterContract for Basket {
+        fn count(&self) -> u32 {
+            5
+        }
+    }
+    use labelled::{Labelled, RecordingCounterContract};
+    let recorder = Recorder::new();
+    assert_eq!(labelled::bind(RecordingCounterContract::new(Basket, &recorder), "plums", 2).describe(), "plums: 10");
+    assert_eq!(recorder.transcript().calls[0].result, 5);
 }
 
 fn test_account() {
@@ -102,17 +285,874 @@ fn test_account() {
     account.withdraw(String::from("Withdrawal 2"), 100);
     println!("Balance: {}", account.balance()); //access to role contract methods incorrectly rewritten as if it was a role method access
     assert_eq!(account.balance(), 150);
+    assert_eq!(account.get_account_no(), 67676555);
+    assert_eq!(account.statement()[0], "#67676555 Deposit 1");
+    assert_eq!(account.ledger_describe(0).as_deref(), Some("#67676555 Deposit 1"));
+    assert_eq!(futures::executor::block_on(account.describe_later(0)).as_deref(), Some("#67676555 Deposit 1"));
+    assert_eq!(futures::executor::block_on(account.describe_later(99)), None);
+
+    account.deposit_all(vec![(String::from("Deposit 3"), 10), (String::from("Deposit 4"), 20)]);
+    assert!(!account.charged_fee());
+    account.transfer_fee(5);
+    assert!(account.charged_fee());
+    assert_eq!(account.balance(), 175);
+    let entries = account.describe_entries();
+    assert_eq!(entries[0], "Deposit 1 +100");
+    assert_eq!(entries.last().map(String::as_str), Some("#67676555 Fee"));
     //println!("Balance: {}", account.balance());
 }
 
-#[async_trait::async_trait]
-impl crate::user_profile_context::UserStorageContract for crate::storage::Context<UserProfile,Serialiser,Encrypter,InMemoryStore> {
-    async fn store(&self, key: String, data: UserProfile) -> Result<String, String> {
-        <Self as Storage<UserProfile, _, _, _>>::store(self, key, &data).await
+fn test_tree() {
+    #[derive(Clone)]
+    struct Node {
+        value: i32,
+        children: Vec<Node>,
+    }
+
+    impl tree::NodeContract for Node {
+        fn value(&self) -> i32 {
+            self.value
+        }
+        fn children(&self) -> Vec<Self> {
+            self.children.clone()
+        }
+    }
+
+    struct Leaf(i32);
+
+    impl tree::NodeContract for Leaf {
+        fn value(&self) -> i32 {
+            self.0
+        }
+        fn children(&self) -> Vec<Self> {
+            Vec::new()
+        }
+    }
+
+    use tree::Tree;
+    let leaf = |value| Node { value, children: Vec::new() };
+    let root = Node {
+        value: 1,
+        children: vec![leaf(2), Node { value: 3, children: vec![leaf(4)] }],
+    };
+
+    let mut context = tree::bind(root);
+    assert_eq!(context.sum(), 10);
+
+    // Recast the node role with a player of the same type and get the previous one back
+    let previous = context.replace_node(leaf(5));
+    assert_eq!(previous.value, 1);
+    assert_eq!(context.sum(), 5);
+
+    // Recast the node role with a player of a different type
+    let mut context = context.with_node(Leaf(7));
+    assert_eq!(context.sum(), 7);
+    assert_eq!(context.into_sum(), 7);
+}
+
+fn test_contractless_roles() {
+    use notification::Notification;
+    let context = notification::bind(String::from("email"), "Hello", ());
+    assert_eq!(context.send(), "[Hello] via email");
+
+    struct Upper;
+    impl supertrait_test::DataContract for Upper {
+        fn process(&self, data: &str) -> String {
+            data.to_uppercase()
+        }
+    }
+
+    use supertrait_test::SupertraitTest;
+    let context = supertrait_test::bind(Upper);
+    assert_eq!(context.process_data("data"), "Transformed: DATA");
+}
+
+fn test_imported_contracts() {
+    #[derive(Default)]
+    struct Lines(Vec<String>);
+    impl shared::LogContract for Lines {
+        fn write(&mut self, line: String) {
+            self.0.push(line);
+        }
+        fn lines(&self) -> Vec<String> {
+            self.0.clone()
+        }
+    }
+    struct Items(Vec<u8>);
+    impl shared::CounterContract for Items {
+        fn count(&self) -> usize {
+            self.0.len()
+        }
+    }
+
+    use audited::Audited;
+    let mut context = audited::bind(Items(vec![1, 2, 3]), Lines::default(), Lines::default());
+    assert_eq!(context.run(), "3 items");
+    assert_eq!(context.log_lines(), vec!["3 items".to_string()]);
+    assert_eq!(context.audit_trail(), vec!["audit: run".to_string()]);
+}
+
+fn test_transfer() {
+    struct Account(i64);
+    impl transfer::AccountContract for Account {
+        fn balance(&self) -> i64 {
+            self.0
+        }
+        fn set_balance(&mut self, balance: i64) {
+            self.0 = balance;
+        }
+    }
+
+    use transfer::Transfer;
+    let mut context = transfer::bind(Account(100), Account(10), 30, 0);
+    assert_eq!(context.transfer(50), 30);
+    assert_eq!(context.transfer(50), 60);
+    assert_eq!(context.balances(), (40, 70));
+}
+
+fn test_no_std_contexts() {
+    use test_no_std::executor::block_on;
+    use test_no_std::pipeline::{self, Pipeline};
+    use test_no_std::sensor::{self, Sensor};
+
+    struct Probe(i32);
+    impl sensor::ProbeContract for Probe {
+        fn sample(&mut self) -> i32 {
+            self.0 += 1;
+            self.0
+        }
+    }
+    struct Log(Vec<i32>);
+    impl sensor::LogContract for Log {
+        fn record(&mut self, reading: i32) {
+            self.0.push(reading);
+        }
+        fn readings(&self) -> Vec<i32> {
+            self.0.clone()
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    struct Celsius;
+    let mut context = sensor::bind::<Celsius, _, _>(Probe(0), Log(vec![]), 10);
+    // Samples 1, 2 and 3 offset by 10
+    assert_eq!(context.measure(3), 12);
+    assert_eq!(block_on(context.measure_settled()), 14);
+    assert_eq!(context.measure(0), 12);
+
+    struct Source;
+    impl pipeline::SourceContract for Source {
+        fn read(&self) -> String {
+            "no_std".to_string()
+        }
+    }
+    struct Upper;
+    impl pipeline::StageContract for Upper {
+        fn apply(&self, input: &str) -> String {
+            input.to_uppercase()
+        }
+    }
+    let context = pipeline::bind(Source, Upper);
+    assert_eq!(block_on(context.process()), "NO_STD");
+}
+
+fn test_expand() {
+    let expanded = five_core::expand(quote::quote! {
+        #[five::context]
+        pub mod counter {
+            pub trait TallyContract {
+                fn add(&mut self, amount: u32);
+            }
+            trait TallyRole: TallyContract {}
+            struct Context {
+                tally: TallyRole,
+            }
+            impl Context {
+                fn count(&mut self) {
+                    self.tally.add(1)
+                }
+            }
+        }
+    })
+    .expect("the context should expand")
+    .to_string();
+    assert!(expanded.contains("pub trait Counter < TTally"));
+    assert!(expanded.contains("pub fn bind"));
+    assert!(!expanded.contains("five :: context"));
+
+    // Problems are reported with their spans instead of panicking or emitting `compile_error!`
+    let errors = five_core::expand(quote::quote! {
+        pub mod empty {
+            struct Context {}
+        }
+    })
+    .expect_err("a context without properties should be rejected");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].to_string(), "Context struct must have at least one property");
+
+    // Receivers other than `self`, `&self` and `&mut self` are reported on their type
+    let errors = five_core::expand(quote::quote! {
+        pub mod boxed {
+            pub trait TallyContract {
+                fn add(&mut self, amount: u32);
+            }
+            trait TallyRole: TallyContract {}
+            struct Context {
+                tally: TallyRole,
+            }
+            impl Context {
+                fn count(self: Box<Self>) {}
+            }
+        }
+    })
+    .expect_err("a boxed receiver should be rejected");
+    assert!(errors[0].to_string().starts_with("only `self`, `&self` and `&mut self` receivers are supported"));
+
+    // A malformed role attribute is a diagnostic on the attribute, not a panic
+    let errors = five_core::expand(quote::quote! {
+        pub mod misspelt {
+            #[role(contact = crate::shared::LedgerContract)]
+            trait LedgerRole {}
+            struct Context {
+                ledger: LedgerRole,
+            }
+            impl Context {
+                fn count(&self) {}
+            }
+        }
+    })
+    .expect_err("a malformed role attribute should be rejected");
+    assert_eq!(errors[0].to_string(), "expected `contract = path::to::Contract`");
+
+    // Names the context generates for itself can't be taken by the user's code
+    let collisions = |field: proc_macro2::TokenStream, interaction: proc_macro2::TokenStream, item: proc_macro2::TokenStream| {
+        let errors = five_core::expand(quote::quote! {
+            pub mod counter {
+                pub trait TallyContract {
+                    fn add(&mut self, amount: u32);
+                }
+                trait TallyRole: TallyContract {}
+                struct Context {
+                    tally: TallyRole,
+                    #field
+                }
+                impl Context {
+                    #interaction
+                }
+                #item
+            }
+        })
+        .expect_err("the name should be rejected");
+        errors.iter().map(ToString::to_string).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        collisions(quote::quote!(), quote::quote!(fn count<TTally>(&self) {}), quote::quote!()),
+        ["`TTally` is the generic parameter generated for the player of the role `tally`, rename this parameter"]
+    );
+    assert_eq!(
+        collisions(quote::quote!(), quote::quote!(fn count<__TNewTally>(&self) {}), quote::quote!()),
+        ["`__TNewTally` is the generic parameter generated for the new player of the role `tally`, rename this parameter"]
+    );
+    assert_eq!(
+        collisions(quote::quote!(), quote::quote!(fn with_tally(&self) {}), quote::quote!()),
+        ["`with_tally` is generated for recasting the role `tally`, rename this interaction"]
+    );
+    assert_eq!(
+        collisions(quote::quote!(), quote::quote!(fn replace_tally(&self) {}), quote::quote!()),
+        ["`replace_tally` is generated for recasting the role `tally`, rename this interaction"]
+    );
+    assert_eq!(
+        collisions(quote::quote!(__five_phantom_count: u32,), quote::quote!(fn count(&self) {}), quote::quote!()),
+        ["field names starting with `__five_phantom_` are reserved for generated fields"]
+    );
+    let items = [
+        ("bind", quote::quote!(pub fn bind() {})),
+        ("DESCRIPTOR", quote::quote!(const DESCRIPTOR: u32 = 0;)),
+        ("Counter", quote::quote!(use std::fmt::Debug as Counter;)),
+    ];
+    for (name, item) in items {
+        assert_eq!(
+            collisions(quote::quote!(), quote::quote!(fn count(&self) {}), item),
+            [format!("`{}` is generated for the context `counter`, rename this item", name)]
+        );
+    }
+
+    // Errors in role calls point at the call in the user's code
+    let source = "
+        pub mod counter {
+            pub trait TallyContract {
+                fn add(&mut self, amount: u32);
+            }
+            trait TallyRole: TallyContract {
+                fn bump(&mut self) {}
+            }
+            struct Context {
+                tally: TallyRole,
+                total: u32,
+            }
+            impl Context {
+                fn count(&mut self) {
+                    TallyRole::bump(&mut self.total)
+                }
+            }
+        }";
+    let errors = five_core::expand(source.parse().unwrap()).expect_err("the call should be rejected");
+    assert_eq!(errors[0].to_string(), "the first argument of `TallyRole::bump` must be the role player `self.tally`");
+    let start = errors[0].span().expect("the error should have a span").start();
+    assert_eq!((start.line, start.column), (15, 31));
+
+    // `#[async_trait]` contracts only box the futures of the context, and require `Send + Sync`
+    // players, when the context opts in with `send`
+    let async_context = |send: bool| {
+        let attr = match send {
+            true => quote::quote!(#[five::context(send)]),
+            false => quote::quote!(#[five::context]),
+        };
+        five_core::expand(quote::quote! {
+            #attr
+            pub mod fetch {
+                #[async_trait::async_trait]
+                pub trait SourceContract {
+                    async fn get(&self) -> u32;
+                }
+                trait SourceRole: SourceContract {}
+                struct Context {
+                    source: SourceRole,
+                }
+                impl Context {
+                    async fn fetch(&self) -> u32 {
+                        self.source.get().await
+                    }
+                }
+            }
+        })
+        .expect("the context should expand")
+        .to_string()
+    };
+    let native = async_context(false);
+    assert!(!native.contains(":: core :: marker :: Send"));
+    assert!(native.contains("async fn fetch"));
+    let boxed = async_context(true);
+    assert!(boxed.contains(":: core :: marker :: Send + :: core :: marker :: Sync"));
+    assert!(boxed.contains("# [async_trait :: async_trait] pub trait Fetch"));
+
+    // Role calls can't be rewritten inside macros whose arguments aren't expressions, a
+    // macro mentioning `self` is reported rather than calling the player directly
+    let errors = five_core::expand(quote::quote! {
+        #[five::context]
+        pub mod tally {
+            pub trait CounterContract {
+                fn count(&self) -> u32;
+            }
+            trait CounterRole: CounterContract {
+                fn doubled(&self) -> u32 {
+                    self.count() * 2
+                }
+            }
+            struct Context {
+                counter: CounterRole,
+            }
+            impl Context {
+                fn report(&self) -> String {
+                    custom_report!(doubled => self.counter.doubled())
+                }
+            }
+        }
+    })
+    .expect_err("the macro should be reported");
+    assert_eq!(
+        errors[0].to_string(),
+        "the arguments of `custom_report!` could not be parsed as expressions, so role methods and `self` \
+         inside it cannot be rewritten; bind the value to a local variable outside the macro and use that instead"
+    );
+
+    // The bounds of the context's parameters stay where they're written, inline bounds
+    // aren't copied into the where clause and the where clause isn't repeated
+    let module = five_core::analyze(quote::quote! {
+        #[five::context]
+        pub mod tagged {
+            struct Context<TTag: Clone>
+            where
+                TTag: std::fmt::Debug,
+            {
+                tag: TTag,
+            }
+            impl<TTag: Clone> Context<TTag>
+            where
+                TTag: std::fmt::Debug,
+            {
+                fn tag(&self) -> TTag {
+                    self.tag.clone()
+                }
+            }
+        }
+    })
+    .unwrap_or_else(|errors| panic!("{}", errors[0]));
+    let generics = &module.context.generics;
+    let params = generics.get_params();
+    assert_eq!(quote::quote!(#(#params),*).to_string(), "TTag : Clone");
+    let where_clause = generics.get_where_clause();
+    assert_eq!(quote::quote!(#where_clause).to_string(), "where TTag : std :: fmt :: Debug ,");
+}
+
+fn test_inventory() {
+    use five_cli::inventory::ContextInventory;
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/transfer.rs");
+    let sources = five_cli::sources::find_contexts(&path).expect("the sources should be readable");
+    assert_eq!(sources.len(), 1);
+    let module = five_core::analyze(sources[0].tokens())
+        .unwrap_or_else(|errors| panic!("{}", errors[0]));
+    let inventory = ContextInventory::new(&sources[0], &module);
+
+    assert_eq!(inventory.module, "crate::transfer");
+    assert_eq!(inventory.context_trait, "Transfer<TSource, TDestination>");
+    let roles: Vec<(&str, &str)> = inventory
+        .roles
+        .iter()
+        .map(|role| (role.field.as_str(), role.player.as_str()))
+        .collect();
+    assert_eq!(roles, [("source", "TSource"), ("destination", "TDestination")]);
+    assert_eq!(inventory.roles[0].contracts, ["AccountContract"]);
+    assert!(inventory.roles[0].methods.contains(&"fn withdraw(&mut self, amount: i64) -> i64".to_string()));
+    assert!(inventory.interactions.contains(&"fn transfer(&mut self, amount: i64) -> i64".to_string()));
+
+    let json = serde_json::to_value(&inventory).unwrap();
+    assert_eq!(json["data"][0]["name"], "limit");
+}
+
+fn test_expand_command() {
+    use five_cli::expand::Expansion;
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/account.rs");
+    let sources = five_cli::sources::find_contexts(&path).expect("the sources should be readable");
+    let expansion = Expansion::new(&sources[0]).unwrap_or_else(|errors| panic!("{}", errors[0]));
+
+    assert!(expansion.code.contains("pub trait Account<TLedger: LedgerContract>: ::five::Describe {"));
+    assert!(expansion.code.contains("pub fn bind<TLedger: LedgerContract>("));
+    // The interaction as written, next to the call through the role trait it becomes
+    assert!(expansion.mapping.contains("== Context::deposit -> Account::deposit\n"));
+    assert!(expansion.mapping.contains(
+        "    self.ledger.add(LedgerEntry::Deposit(message, amount)) |     \
+         <Self as LedgerRole<TLedger>>::add(&mut *self, LedgerEntry::Deposit(message, amount))"
+    ));
+
+    let dir = std::env::temp_dir().join(format!("five-expand-{}", std::process::id()));
+    let (code, mapping) = expansion.write(&dir, "account").expect("the expansion should be written");
+    assert_eq!(std::fs::read_to_string(code).unwrap(), expansion.code);
+    assert_eq!(std::fs::read_to_string(mapping).unwrap(), expansion.mapping);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+fn test_descriptor() {
+    let descriptor = account::DESCRIPTOR;
+    assert_eq!(descriptor.name, "account");
+    assert_eq!(descriptor.context_trait, "Account");
+
+    let ledger = descriptor.role("ledger").expect("the ledger should be a role");
+    assert_eq!(descriptor.role("LedgerRole"), Some(ledger));
+    assert_eq!(ledger.contracts, ["LedgerContract"]);
+    assert_eq!(ledger.contract_methods[0].signature, "fn push(&mut self, entry: LedgerEntry)");
+    assert_eq!(
+        ledger.methods.iter().map(|method| method.name).collect::<Vec<_>>(),
+        ["add", "log", "describe"]
+    );
+    assert_eq!(descriptor.data[0], five::DataDescriptor { name: "account_no", ty: "i64" });
+    assert_eq!(
+        descriptor.interaction("deposit").map(|interaction| interaction.signature),
+        Some("fn deposit(&mut self, message: String, amount: i32)")
+    );
+    assert!(descriptor.interaction("with_ledger").is_none());
+
+    // Bound contexts describe themselves through the context trait
+    struct Ledger;
+    impl LedgerContract for Ledger {
+        fn push(&mut self, _: account::LedgerEntry) {}
+        fn as_vec(&self) -> Vec<account::LedgerEntry> {
+            vec![]
+        }
+    }
+    use five::Describe;
+    let bound = account::bind(Ledger, 1);
+    assert_eq!(bound.descriptor(), &account::DESCRIPTOR);
+
+    // Descriptors need neither `std` nor `alloc`, and the generated phantom fields aren't data
+    let sensor = test_no_std::sensor::DESCRIPTOR;
+    assert_eq!(sensor.role("probe").unwrap().methods[1].signature, "async fn settled(&mut self) -> i32");
+    assert_eq!(sensor.data, [five::DataDescriptor { name: "offset", ty: "i32" }]);
+}
+
+fn test_manifest() {
+    use five_cli::manifest::{crate_name, Manifest, SCHEMA_VERSION};
+
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+    let sources = five_cli::sources::find_contexts(dir).expect("the sources should be readable");
+    let manifest = Manifest::from_sources(crate_name(dir), &sources).unwrap_or_else(|errors| panic!("{}", errors[0]));
+    let json: serde_json::Value = serde_json::from_str(&manifest.to_json()).unwrap();
+    assert_eq!(json["schema_version"], SCHEMA_VERSION);
+    assert_eq!(json["crate"], "test");
+
+    let context = |name: &str| {
+        json["contexts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|context| context["name"] == name)
+            .unwrap_or_else(|| panic!("{} should be in the manifest", name))
+    };
+
+    // `log` reads the balance, an interaction using the ledger's `as_vec`
+    let ledger = &context("account")["roles"][0];
+    assert_eq!(ledger["field"], "ledger");
+    assert_eq!(
+        ledger["methods"][1]["uses"],
+        serde_json::json!([{ "field": "ledger", "contract": "LedgerContract", "method": "as_vec" }])
+    );
+    assert_eq!(context("account")["contracts"][0]["methods"][0]["name"], "push");
+
+    // Every contract method used is listed among the contracts of the role it's called on,
+    // including contracts that aren't named after the role
+    let mut uses = vec![];
+    for context in json["contexts"].as_array().unwrap() {
+        let roles = context["roles"].as_array().unwrap();
+        let used_methods = roles
+            .iter()
+            .flat_map(|role| role["methods"].as_array().unwrap())
+            .flat_map(|method| method["uses"].as_array().unwrap());
+        for used in used_methods {
+            let role = roles.iter().find(|role| role["field"] == used["field"]).unwrap();
+            assert!(
+                role["contracts"].as_array().unwrap().contains(&used["contract"]),
+                "{} isn't among the contracts of {}",
+                used["contract"],
+                role["name"]
+            );
+            uses.push(format!("{}::{}", used["contract"].as_str().unwrap(), used["method"].as_str().unwrap()));
+        }
+    }
+    assert!(uses.contains(&"AccountContract::set_balance".to_string()));
+    assert!(uses.contains(&"std::fmt::Display::to_string".to_string()));
+
+    // The storage context provides the contract of the user profile context's storage role
+    assert_eq!(
+        context("storage")["plays"],
+        serde_json::json!([{
+            "contract": "crate::user_profile_context::UserStorageContract",
+            "context": "crate::user_profile_context::user_profile_context",
+            "role": "UserStorageRole"
+        }])
+    );
+
+    // Keys that merely start with `name` are skipped, an inherited name comes from the workspace
+    let workspace = std::env::temp_dir().join(format!("five-manifest-{}", std::process::id()));
+    let package = workspace.join("member");
+    std::fs::create_dir_all(&package).unwrap();
+    std::fs::write(
+        workspace.join("Cargo.toml"),
+        "[workspace]\nmembers = [\"member\"]\n\n[workspace.package]\nname = \"inherited\"\n",
+    )
+    .unwrap();
+    std::fs::write(package.join("Cargo.toml"), "[package]\nnamespace = \"other\"\nname = \"member\"\n").unwrap();
+    assert_eq!(crate_name(&package).as_deref(), Some("member"));
+    std::fs::write(package.join("Cargo.toml"), "[package]\nname.workspace = true\n").unwrap();
+    assert_eq!(crate_name(&package).as_deref(), Some("inherited"));
+    std::fs::remove_dir_all(&workspace).unwrap();
+}
+
+fn test_diagram() {
+    use five_cli::diagram;
+    use five_core::analysis::{analyze_call_graph, Callee};
+
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/account.rs");
+    let sources = five_cli::sources::find_contexts(&path).expect("the sources should be readable");
+    let module = five_core::analyze(sources[0].tokens()).unwrap_or_else(|errors| panic!("{}", errors[0]));
+    let interactions = analyze_call_graph(&module.context);
+
+    // deposit -> LedgerRole::add -> LedgerContract::push
+    let deposit = interactions.iter().find(|calls| calls.interaction == "deposit").unwrap();
+    let add = &deposit.calls[0];
+    assert_eq!(
+        add.callee,
+        Callee::RoleMethod {
+            field: "ledger".to_string(),
+            role: "LedgerRole".to_string(),
+            method: "add".to_string()
+        }
+    );
+    assert_eq!(
+        add.calls[0].callee,
+        Callee::PlayerMethod {
+            field: "ledger".to_string(),
+            role: "LedgerRole".to_string(),
+            contract: "LedgerContract".to_string(),
+            method: "push".to_string()
+        }
+    );
+
+    let mermaid = diagram::mermaid("Account", &interactions);
+    assert!(mermaid.contains("## Account::deposit\n\n```mermaid\nsequenceDiagram\n"));
+    assert!(mermaid.contains("    participant ledger as ledger: LedgerRole\n    Context->>ledger: add\n"));
+    assert!(mermaid.contains("    ledger->>ledger: LedgerContract::push\n"));
+
+    let dot = diagram::dot("Account", &interactions);
+    assert!(dot.starts_with("digraph \"Account\" {"));
+    assert!(dot.contains("    \"Account::deposit\" -> \"ledger: LedgerRole::add\";\n"));
+    assert!(dot.contains("    \"ledger: LedgerRole::add\" -> \"ledger: LedgerContract::push\";\n"));
+    // Edges are listed once however often the call is made
+    assert_eq!(dot.matches("\"Account::statement\" -> \"ledger: LedgerRole::describe\"").count(), 1);
+
+    // Players only called through their contract are labelled with their role as well, and
+    // the calls with the contract declaring the method
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/transfer.rs");
+    let sources = five_cli::sources::find_contexts(&path).expect("the sources should be readable");
+    let module = five_core::analyze(sources[0].tokens()).unwrap_or_else(|errors| panic!("{}", errors[0]));
+    let interactions = analyze_call_graph(&module.context);
+    let mermaid = diagram::mermaid("Transfer", &interactions);
+    assert!(mermaid.contains(
+        "## Transfer::balances\n\n```mermaid\nsequenceDiagram\n    participant Context as Transfer\n    \
+         participant source as source: SourceRole\n    participant destination as destination: DestinationRole\n"
+    ));
+    assert!(mermaid.contains("    Context->>destination: AccountContract::balance\n"));
+}
+
+fn test_conditional_interactions() {
+    struct Basket;
+    impl labelled::CounterContract for Basket {
+        fn count(&self) -> u32 {
+            3
+        }
+    }
+    #[derive(Clone, PartialEq, Debug)]
+    struct Tag(u8);
+
+    use labelled::Labelled;
+    let context = labelled::bind(Basket, "apples", 2);
+    assert_eq!(context.label(), "apples");
+    assert_eq!(context.describe(), "apples: 6");
+
+    // `Tag` isn't `Display`, so only the unconditional interactions are available
+    let context = labelled::bind(Basket, Tag(7), 1);
+    assert_eq!(context.label(), Tag(7));
+
+    // Lifetime bounds of the impl blocks are kept as well
+    struct Width(usize);
+    impl window::SourceContract for Width {
+        fn len(&self) -> usize {
+            self.0
+        }
+    }
+    use window::Window;
+    let (first, second) = ("short".to_string(), "longer".to_string());
+    let context = window::bind(Width(5), &first, &second);
+    assert_eq!(context.longer(), "longer");
+    assert_eq!(context.fitting(), Some("short"));
+}
+
+async fn test_send_futures() {
+    struct Shelf;
+    impl inventory::StockContract for Shelf {
+        fn count(&self, item: &str) -> u32 {
+            if item == "apple" { 3 } else { 0 }
+        }
+    }
+
+    use inventory::Inventory;
+    let context = inventory::bind(Shelf, "apple".to_string());
+    let available = tokio::spawn(async move { context.check().await }).await.unwrap();
+    assert!(available);
+}
+
+/// Collects the spans created and the events recorded, with the span they're recorded in
+#[derive(Clone, Default)]
+struct Collector {
+    records: Arc<Mutex<Vec<String>>>,
+    spans: Arc<Mutex<Vec<String>>>,
+    entered: Arc<Mutex<Vec<u64>>>,
+}
+
+#[derive(Default)]
+struct Fields(Vec<String>);
+
+impl tracing::field::Visit for Fields {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0.push(format!("{}={:?}", field.name(), value));
+    }
+}
+
+impl tracing::Subscriber for Collector {
+    fn enabled(&self, _metadata: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        self.records.lock().unwrap().push(format!("span {} {}", span.metadata().name(), fields.0.join(" ")));
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(span.metadata().name().to_string());
+        tracing::span::Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _span: &tracing::span::Id, _values: &tracing::span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &tracing::span::Id, _follows: &tracing::span::Id) {}
+
+    fn event(&self, event: &tracing::Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let span = match self.entered.lock().unwrap().last() {
+            Some(id) => self.spans.lock().unwrap()[*id as usize - 1].clone(),
+            None => "none".to_string(),
+        };
+        self.records.lock().unwrap().push(format!("event in {} {}", span, fields.0.join(" ")));
+    }
+
+    fn enter(&self, span: &tracing::span::Id) {
+        self.entered.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _span: &tracing::span::Id) {
+        self.entered.lock().unwrap().pop();
+    }
+}
+
+async fn test_tracing() {
+    struct Shelf;
+    impl inventory::StockContract for Shelf {
+        fn count(&self, item: &str) -> u32 {
+            if item == "apple" { 3 } else { 0 }
+        }
+    }
+
+    let collector = Collector::default();
+    let _guard = tracing::subscriber::set_default(collector.clone());
+
+    use inventory::Inventory;
+    let context = inventory::bind(Shelf, "apple".to_string());
+    assert!(context.check().await);
+
+    let records = collector.records.lock().unwrap().clone();
+    assert_eq!(
+        records,
+        [
+            "span inventory::check context=\"inventory\"",
+            "span inventory::StockRole::reserve context=\"inventory\" role=\"StockRole\"",
+            "span inventory::StockRole::available context=\"inventory\" role=\"StockRole\"",
+            "event in inventory::StockRole::available message=contract call contract=\"StockContract\" \
+             method=\"count\" role=\"StockRole\"",
+        ]
+    );
+
+    // Calls on an alias of the player and through the contract are recorded as well
+    collector.records.lock().unwrap().clear();
+    assert_eq!(context.total("pear"), 3);
+    let records = collector.records.lock().unwrap().clone();
+    let event = "event in inventory::total message=contract call contract=\"StockContract\" method=\"count\" \
+                 role=\"StockRole\"";
+    assert_eq!(records, ["span inventory::total context=\"inventory\"", event, event]);
+
+    // The events name the supertrait declaring the method, not the role, when the contract
+    // isn't named after the role
+    let traced = five_core::expand(quote::quote! {
+        #[five::context(trace)]
+        pub mod payment {
+            pub trait AccountContract {
+                fn balance(&self) -> i64;
+            }
+            trait PayerRole: AccountContract + std::fmt::Display {}
+            struct Context {
+                payer: PayerRole,
+            }
+            impl Context {
+                fn describe(&self) -> String {
+                    format!("{} has {}", self.payer.to_string(), self.payer.balance())
+                }
+            }
+        }
+    })
+    .expect("the context should expand")
+    .to_string();
+    assert!(traced.contains(r#"contract = "AccountContract" , method = "balance""#));
+    assert!(traced.contains(r#"contract = "std::fmt::Display" , method = "to_string""#));
+}
+
+static AUDIT: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Intercepts the interactions of the metered context
+pub struct Audit;
+
+impl five::Interceptor for Audit {
+    fn before_interaction(interaction: &five::Interaction) {
+        AUDIT.lock().unwrap().push(format!("before {}::{}", interaction.context.name, interaction.name));
+    }
+
+    fn after_interaction(interaction: &five::Interaction) {
+        AUDIT.lock().unwrap().push(format!("after {}::{}", interaction.context.name, interaction.name));
     }
-    async fn retrieve(&self, key: String) -> Result<UserProfile, String> {
-        <Self as Storage<UserProfile, _, _, _>>::retrieve(self, key).await
+
+    fn on_error(interaction: &five::Interaction, error: &dyn std::fmt::Debug) {
+        AUDIT.lock().unwrap().push(format!("error {}::{}: {:?}", interaction.context.name, interaction.name, error));
+    }
+}
+
+async fn test_interceptor() {
+    struct Meter(u32);
+    impl metered::MeterContract for Meter {
+        fn reading(&self) -> u32 {
+            self.0
+        }
     }
+
+    AUDIT.lock().unwrap().clear();
+    use metered::Metered;
+    let context = metered::bind(Meter(5), 10);
+    assert_eq!(context.read(), 5);
+    assert_eq!(context.check(), Ok(5));
+    assert_eq!(
+        std::mem::take(&mut *AUDIT.lock().unwrap()),
+        [
+            "before metered::read",
+            "after metered::read",
+            "before metered::check",
+            "after metered::check",
+        ]
+    );
+
+    // Errors, returned with `?` or not, reach `on_error` before `after_interaction`
+    let context = metered::bind(Meter(20), 10);
+    assert_eq!(context.check(), Err("20 is over the limit".to_string()));
+    assert_eq!(context.check_later().await, Err("20 is over the limit".to_string()));
+    assert_eq!(
+        std::mem::take(&mut *AUDIT.lock().unwrap()),
+        [
+            "before metered::check",
+            "error metered::check: \"20 is over the limit\"",
+            "after metered::check",
+            "before metered::check_later",
+            "error metered::check_later: \"20 is over the limit\"",
+            "after metered::check_later",
+        ]
+    );
+
+    // Recasting a role isn't an interaction, the binders aren't intercepted
+    let context = context.with_meter(Meter(7));
+    let mut context = context.with_meter(Meter(20));
+    assert_eq!(context.replace_meter(Meter(30)).0, 20);
+    assert!(AUDIT.lock().unwrap().is_empty());
+
+    // `after_interaction` is called when the interaction panics as well
+    let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| context.read_within_limit()));
+    assert!(panicked.is_err());
+    assert_eq!(
+        std::mem::take(&mut *AUDIT.lock().unwrap()),
+        ["before metered::read_within_limit", "after metered::read_within_limit"]
+    );
+
+    let interaction = five::Interaction::new(&metered::DESCRIPTOR, "check_later");
+    assert_eq!(
+        interaction.descriptor().unwrap().signature,
+        "async fn check_later(&self) -> Result<u32, String>"
+    );
 }
 
 async fn test_user_profile_context() {
@@ -120,18 +1160,13 @@ async fn test_user_profile_context() {
     let encrypter = Encrypter;
     let store = InMemoryStore;
     
-    // Create a concrete storage context
-    let storage = storage::bind::<UserProfile, _, _, _>(serialiser, encrypter, store);
-    
-    // Cast to concrete type
-    let concrete_storage: crate::storage::Context<UserProfile,Serialiser,Encrypter,InMemoryStore> = unsafe { 
-        std::mem::transmute(storage) 
-    };
+    // The storage context plays the user storage role of the user profile context
+    let storage = storage::bind_user_storage(serialiser, encrypter, store);
     
     // Import the traits we need
     use crate::user_profile_context::UserProfileContext;
     
-    let context = crate::user_profile_context::bind(concrete_storage);
+    let context = crate::user_profile_context::bind(storage);
     
     // Create a user profile with the required 'sub' field
     let user_profile = UserProfile::new("user123".to_string())
@@ -0,0 +1,43 @@
+// Every interaction runs between the hooks of `crate::Audit`
+#[five::context(intercept(crate::Audit))]
+pub mod metered {
+    pub trait MeterContract {
+        fn reading(&self) -> u32;
+    }
+
+    trait MeterRole: MeterContract {
+        fn checked_reading(&self) -> Result<u32, String> {
+            let reading = self.reading();
+            if reading > self.context.limit {
+                Err(format!("{} is over the limit", reading))
+            } else {
+                Ok(reading)
+            }
+        }
+    }
+
+    struct Context {
+        meter: MeterRole,
+        limit: u32,
+    }
+
+    impl Context {
+        fn read(&self) -> u32 {
+            self.meter.reading()
+        }
+        fn check(&self) -> Result<u32, String> {
+            let reading = self.meter.checked_reading()?;
+            Ok(reading)
+        }
+        async fn check_later(&self) -> Result<u32, String> {
+            tokio::task::yield_now().await;
+            self.meter.checked_reading()
+        }
+        // Panics over the limit, e.g. a broken invariant
+        fn read_within_limit(&self) -> u32 {
+            let reading = self.meter.reading();
+            assert!(reading <= self.limit, "{} is over the limit", reading);
+            reading
+        }
+    }
+}
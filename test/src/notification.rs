@@ -0,0 +1,33 @@
+#[five::context]
+pub mod notification {
+    // A role whose contract is composed from standard traits
+    trait MessageRole: std::fmt::Display + Clone {
+        fn render(&self) -> String {
+            let text = self.to_string();
+            format!("[{}]", text)
+        }
+    }
+
+    // A role without a contract, relying only on the other roles and the context data
+    trait NotifierRole {
+        fn notify(&self) -> String {
+            let message = self.context.message.render();
+            let channel = self.context.channel.clone();
+            format!("{} via {}", message, channel)
+        }
+    }
+
+    struct Context {
+        channel: String,
+        message: MessageRole,
+        // The notifier only contributes its role methods, the player itself is never used
+        #[allow(dead_code)]
+        notifier: NotifierRole,
+    }
+
+    impl Context {
+        pub fn send(&self) -> String {
+            self.notifier.notify()
+        }
+    }
+}
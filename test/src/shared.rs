@@ -0,0 +1,19 @@
+// Contracts and roles shared by several contexts
+
+pub trait LogContract {
+    fn write(&mut self, line: String);
+    fn lines(&self) -> Vec<String>;
+}
+
+pub trait CounterContract {
+    fn count(&self) -> usize;
+}
+
+// A role reusable across contexts, any log can play it
+pub trait AuditRole: LogContract {
+    fn audit(&mut self, event: &str) {
+        self.write(format!("audit: {}", event));
+    }
+}
+
+impl<T: LogContract> AuditRole for T {}
@@ -1,5 +1,6 @@
 use aes_gcm::{aead::Aead, Aes256Gcm, KeyInit, Nonce};
 use serde::{Deserialize, Serialize};
+use crate::data::data::UserProfile;
 
 
 #[allow(dead_code)]
@@ -8,23 +9,48 @@ pub enum SerialiserType {
     Cbor
 }
 
-#[five::context]
+// A storage context can play the user storage role of the user profile context
+#[five::context(provides(
+    #[async_trait::async_trait]
+    crate::user_profile_context::UserStorageContract
+    where
+        TContext = UserProfile,
+        TSerialiser: Sync,
+        TEncrypter: Sync,
+    {
+        async fn store(&self, key: String, data: UserProfile) -> Result<String, String> => store(key, &data);
+        async fn retrieve(&self, key: String) -> Result<UserProfile, String>;
+    }
+))]
 pub mod storage {
     pub trait SerialiserContract : {
         fn get_type(&self) -> SerialiserType;
     }
 
+    #[cfg_attr(test, five::mock)]
+    #[five::record]
     #[async_trait::async_trait]
     pub trait StoreContract: Send + Sync {
         async fn store(&self, key: String, data: Vec<u8>) -> Result<String, String>;
         async fn retrieve(&self, key: String) -> Result<Vec<u8>, String>;
     }
+    #[cfg_attr(test, five::mock)]
     pub trait EncrypterContract {
         fn get_key(&self) -> &[u8];
     }
 
     trait StoreRole :  StoreContract{ 
-       
+        // Retries a failing store before giving up
+        async fn store_with_retry(&self, key: String, data: Vec<u8>) -> Result<String, String> {
+            let mut result = self.store(key.clone(), data.clone()).await;
+            for _ in 1..3 {
+                if result.is_ok() {
+                    break;
+                }
+                result = self.store(key.clone(), data.clone()).await;
+            }
+            result
+        }
     }
 
     trait SerialiserRole :  SerialiserContract{ 
@@ -79,7 +105,7 @@ pub mod storage {
         encrypter: EncrypterRole,
         store: StoreRole
     }
-    impl<T: Serialize + for<'de> Deserialize<'de>> Context<TContext> {
+    impl<TContext: Serialize + for<'de> Deserialize<'de>> Context<TContext> {
         #[inline]
         fn should_encrypt(&self) -> bool {
             true
@@ -91,7 +117,7 @@ pub mod storage {
             } else {
                 serialised
             };
-            self.store.store(key, encrypted).await
+            self.store.store_with_retry(key, encrypted).await
         } 
         pub async fn retrieve(&self, key: String) -> Result<TContext, String>{
             let encrypted_data = self.store.retrieve(key).await?;
@@ -0,0 +1,54 @@
+#[five::context]
+pub mod transfer {
+    pub trait AccountContract {
+        fn balance(&self) -> i64;
+        fn set_balance(&mut self, balance: i64);
+    }
+
+    trait SourceRole: AccountContract {
+        // Only borrows the source and the limit, not the whole context
+        fn withdraw(&mut self, amount: i64) -> i64 {
+            // Local names don't clash with the borrowed player and fields
+            let source = self.context.limit;
+            let amount = amount.min(source).min(self.available());
+            self.set_balance(self.balance() - amount);
+            amount
+        }
+        fn available(&self) -> i64 {
+            self.balance()
+        }
+    }
+
+    trait DestinationRole: AccountContract {
+        fn deposit(&mut self, amount: i64) {
+            self.set_balance(self.balance() + amount);
+            self.context.transferred += amount;
+        }
+    }
+
+    struct Context {
+        source: SourceRole,
+        destination: DestinationRole,
+        limit: i64,
+        transferred: i64,
+    }
+
+    impl Context {
+        fn transfer(&mut self, amount: i64) -> i64 {
+            // A shared borrow of the source is held while the destination role is called,
+            // both directly and through an alias
+            let source = &self.source;
+            self.destination.deposit(0);
+            let destination = &mut self.destination;
+            destination.deposit(0);
+            assert!(source.balance() >= 0);
+
+            // Two role calls combined in one expression
+            self.destination.deposit(self.source.withdraw(amount));
+            self.transferred
+        }
+        fn balances(&self) -> (i64, i64) {
+            (self.source.available(), self.destination.balance())
+        }
+    }
+}
@@ -0,0 +1,33 @@
+#[five::context]
+pub mod tree {
+    pub trait NodeContract {
+        fn value(&self) -> i32;
+        fn children(&self) -> Vec<Self>
+        where
+            Self: Sized;
+    }
+
+    trait NodeRole: NodeContract {}
+
+    struct Context {
+        node: NodeRole,
+    }
+
+    impl Context {
+        // Walks the tree by recasting the node role for every child
+        fn sum(&mut self) -> i32 {
+            let mut total = self.node.value();
+            for child in self.node.children() {
+                let parent = self.replace_node(child);
+                total += self.sum();
+                self.replace_node(parent);
+            }
+            total
+        }
+
+        // Consumes the context, recasting the node role on its own copy
+        fn into_sum(mut self) -> i32 {
+            self.sum()
+        }
+    }
+}
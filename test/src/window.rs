@@ -0,0 +1,34 @@
+// Impl blocks may bound the lifetimes of the context as well as its types
+#[five::context]
+pub mod window {
+    pub trait SourceContract {
+        fn len(&self) -> usize;
+    }
+
+    trait SourceRole: SourceContract {
+        fn fits(&self, text: &str) -> bool {
+            text.len() <= self.len()
+        }
+    }
+
+    struct Context<'a, 'b> {
+        source: SourceRole,
+        first: &'a str,
+        second: &'b str,
+    }
+
+    impl<'a, 'b: 'a> Context<'a, 'b> {
+        pub fn longer(&self) -> &'a str {
+            if self.second.len() > self.first.len() { self.second } else { self.first }
+        }
+    }
+
+    impl<'a, 'b> Context<'a, 'b>
+    where
+        'a: 'b,
+    {
+        pub fn fitting(&self) -> Option<&'b str> {
+            [self.first, self.second].into_iter().find(|text| self.source.fits(text))
+        }
+    }
+}
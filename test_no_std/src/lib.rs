@@ -0,0 +1,8 @@
+//! Contexts compiled without `std`, using only `core` and `alloc`
+#![no_std]
+
+extern crate alloc;
+
+pub mod executor;
+pub mod pipeline;
+pub mod sensor;
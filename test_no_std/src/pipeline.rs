@@ -0,0 +1,41 @@
+use alloc::string::String;
+
+use crate::executor::yield_now;
+
+// Interactions return `Send` futures, spelled with `core` paths only
+#[five::context(send)]
+pub mod pipeline {
+    pub trait SourceContract {
+        fn read(&self) -> String;
+    }
+
+    pub trait StageContract {
+        fn apply(&self, input: &str) -> String;
+    }
+
+    trait SourceRole: SourceContract {
+        async fn fetch(&self) -> String {
+            yield_now().await;
+            self.read()
+        }
+    }
+
+    trait StageRole: StageContract {
+        async fn run(&self, input: &str) -> String {
+            yield_now().await;
+            self.apply(input)
+        }
+    }
+
+    struct Context {
+        source: SourceRole,
+        stage: StageRole,
+    }
+
+    impl Context {
+        async fn process(&self) -> String {
+            let input = self.source.fetch().await;
+            self.stage.run(&input).await
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use alloc::vec::Vec;
+
+use crate::executor::yield_now;
+
+#[five::context]
+pub mod sensor {
+    pub trait ProbeContract {
+        fn sample(&mut self) -> i32;
+    }
+
+    pub trait LogContract {
+        fn record(&mut self, reading: i32);
+        fn readings(&self) -> Vec<i32>;
+    }
+
+    trait ProbeRole: ProbeContract {
+        fn calibrated(&mut self) -> i32 {
+            self.sample() + self.context.offset
+        }
+        async fn settled(&mut self) -> i32 {
+            yield_now().await;
+            self.calibrated()
+        }
+    }
+
+    trait LogRole: LogContract {
+        fn average(&self) -> i32 {
+            let readings = self.readings();
+            if readings.is_empty() {
+                0
+            } else {
+                readings.iter().sum::<i32>() / readings.len() as i32
+            }
+        }
+    }
+
+    // `TUnit` only tags the readings, so the context holds it as `PhantomData`
+    struct Context<TUnit: Copy> {
+        probe: ProbeRole,
+        log: LogRole,
+        offset: i32,
+    }
+
+    impl<TUnit: Copy> Context<TUnit> {
+        fn measure(&mut self, samples: usize) -> i32 {
+            for _ in 0..samples {
+                let reading = self.probe.calibrated();
+                self.log.record(reading);
+            }
+            self.log.average()
+        }
+
+        async fn measure_settled(&mut self) -> i32 {
+            let reading = self.probe.settled().await;
+            self.log.record(reading);
+            reading
+        }
+    }
+}